@@ -0,0 +1,161 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+// CPU cost of building and submitting a frame with N cubes, on a headless context.
+// Run with `cargo bench --bench frame_build`.
+
+use std::time::{Duration, Instant};
+
+use cgmath::{Matrix4, Vector3};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use demo_cube_wgpu::draw_context::DrawContext;
+use demo_cube_wgpu::primitives::cube;
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
+use demo_cube_wgpu::scene::Scene3D;
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+
+const CUBE_COUNTS: [usize; 3] = [10, 100, 1000];
+const DEFAULT_CUBE_COUNT: usize = 100;
+const GRID_SPACING: f32 = 2.0;
+
+struct CubeGrid {
+    scene: Scene3D,
+}
+
+impl CubeGrid {
+    fn with_count(context: &DrawContext, count: usize) -> anyhow::Result<Self> {
+        let shader_module = context.create_shader_module("Default Shader", DEFAULT_SHADER)?;
+        let buffers = [context.vertex_buffer_layout.clone()];
+        let targets = [Some(wgpu::ColorTargetState {
+            format: context.surface_format(),
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
+        let side = (count as f32).sqrt().ceil() as usize;
+        let mut scene = Scene3D::new();
+        for index in 0..count {
+            let vertex_state = wgpu::VertexState {
+                module: &shader_module,
+                entry_point: None,
+                buffers: &buffers,
+                compilation_options: Default::default(),
+            };
+            let fragment_state = wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: None,
+                targets: &targets,
+                compilation_options: Default::default(),
+            };
+            let mut cube = cube::create_cube(context, vertex_state, fragment_state);
+            let (x, z) = ((index % side) as f32, (index / side) as f32);
+            cube.set_transform(
+                context,
+                Matrix4::from_translation(Vector3::new(x * GRID_SPACING, 0., z * GRID_SPACING)),
+            );
+            scene.add(cube);
+        }
+        Ok(CubeGrid { scene })
+    }
+}
+
+impl Scenario for CubeGrid {
+    fn new(context: &DrawContext) -> anyhow::Result<Self> {
+        Self::with_count(context, DEFAULT_CUBE_COUNT)
+    }
+}
+
+impl DynScenario for CubeGrid {
+    fn update(&mut self, _context: &DrawContext, _update_interval: &UpdateInterval) {}
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        self.scene.render(render_pass, render_stats);
+    }
+    fn render_depth_prepass<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+    ) {
+        self.scene.render_depth_prepass(render_pass);
+    }
+}
+
+fn frame_build(c: &mut Criterion) {
+    let mut context = match pollster::block_on(DrawContext::new_headless(256, 256)) {
+        Ok(context) => context,
+        Err(err) => {
+            eprintln!("Skipping benchmark, no headless context available: {err:#}");
+            return;
+        }
+    };
+    for (group_name, depth_prepass, opaque_sorting) in [
+        ("frame_build", false, false),
+        ("frame_build_depth_prepass", true, false),
+        ("frame_build_opaque_sorting", false, true),
+    ] {
+        // Pipelines are created with the depth prepass setting, so the grids are built after it
+        context.set_depth_prepass(depth_prepass);
+        bench_cube_counts(c, &context, group_name, opaque_sorting);
+    }
+}
+
+fn bench_cube_counts(
+    c: &mut Criterion,
+    context: &DrawContext,
+    group_name: &str,
+    opaque_sorting: bool,
+) {
+    let mut group = c.benchmark_group(group_name);
+    for count in CUBE_COUNTS {
+        let mut grid = CubeGrid::with_count(context, count).expect("Could not build the scene");
+        // Looking toward -Z, so the rows added along +Z are reversed by the sorting
+        grid.scene.set_opaque_sorting(opaque_sorting);
+        grid.scene
+            .update_camera_view(&Matrix4::from_nonuniform_scale(1., 1., -1.));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter_custom(|iterations| {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iterations {
+                    let start = Instant::now();
+                    context
+                        .render_scene(&mut grid)
+                        .expect("Could not render the scene");
+                    elapsed += start.elapsed();
+                    // Waiting for the GPU is not measured, only keeps the queue from growing
+                    context.device.poll(wgpu::Maintain::Wait);
+                }
+                elapsed
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, frame_build);
+criterion_main!(benches);
@@ -0,0 +1,140 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use demo_cube_wgpu::draw_context::{DrawContext, Drawable, DrawableOptions, Vertex};
+use demo_cube_wgpu::primitives::color::{RED, WHITE};
+use demo_cube_wgpu::primitives::Object3D;
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+
+const ROTATION_DEG_PER_S: f32 = 20.0;
+const GROUND_SIZE: f32 = 3.0;
+const DECAL_SIZE: f32 = 1.0;
+// In units of the depth format precision, and relative to the depth slope of the triangles
+const DEPTH_BIAS_CONSTANT: i32 = 2;
+const DEPTH_BIAS_SLOPE_SCALE: f32 = 1.0;
+
+fn square(size: f32, color: [f32; 3]) -> [Vertex; 6] {
+    let half = size / 2.;
+    [
+        [-half, -half],
+        [-half, half],
+        [half, half],
+        [-half, -half],
+        [half, half],
+        [half, -half],
+    ]
+    .map(|[x, y]| Vertex {
+        position: [x, y, 0.],
+        color,
+    })
+}
+
+// A red decal coplanar with a white ground. The decal is rendered first and would be overwritten
+// by the ground without its depth bias.
+pub struct MainScenario {
+    ground: Object3D,
+    decal: Object3D,
+}
+
+impl Scenario for MainScenario {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
+        let default_shader_module =
+            draw_context.create_shader_module("Fragment Shader", DEFAULT_SHADER)?;
+        let vertex_state = wgpu::VertexState {
+            module: &default_shader_module,
+            entry_point: None,
+            buffers: std::slice::from_ref(&draw_context.vertex_buffer_layout),
+            compilation_options: Default::default(),
+        };
+        let fragment_state = wgpu::FragmentState {
+            module: &default_shader_module,
+            entry_point: None,
+            targets: &[Some(wgpu::ColorTargetState {
+                format: draw_context.surface_format(),
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        };
+        let ground = Drawable::init_direct_with_options(
+            draw_context,
+            &square(GROUND_SIZE, WHITE),
+            vertex_state.clone(),
+            fragment_state.clone(),
+            DrawableOptions {
+                double_sided: true,
+                ..Default::default()
+            },
+        );
+        // Nearer depth is lower, or higher with reversed-Z
+        let bias_sign = match draw_context.is_reverse_z() {
+            true => 1,
+            false => -1,
+        };
+        let decal = Drawable::init_direct_with_options(
+            draw_context,
+            &square(DECAL_SIZE, RED),
+            vertex_state,
+            fragment_state,
+            DrawableOptions {
+                double_sided: true,
+                depth_bias: wgpu::DepthBiasState {
+                    constant: bias_sign * DEPTH_BIAS_CONSTANT,
+                    slope_scale: bias_sign as f32 * DEPTH_BIAS_SLOPE_SCALE,
+                    clamp: 0.,
+                },
+                ..Default::default()
+            },
+        );
+        Ok(Self {
+            ground: Object3D::from_drawable(ground),
+            decal: Object3D::from_drawable(decal),
+        })
+    }
+}
+
+impl DynScenario for MainScenario {
+    fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        let total_seconds = update_interval.scenario_elapsed.as_secs_f32();
+        let transform: cgmath::Matrix4<f32> =
+            cgmath::Matrix4::from_angle_y(cgmath::Deg(ROTATION_DEG_PER_S * total_seconds));
+        self.ground.set_transform(context, transform);
+        self.decal.set_transform(context, transform);
+    }
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        self.decal.as_ref().render(render_pass);
+        self.ground.as_ref().render(render_pass);
+        render_stats.record_draw();
+        render_stats.record_draw();
+    }
+}
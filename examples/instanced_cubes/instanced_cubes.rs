@@ -0,0 +1,114 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{Deg, Quaternion, Rotation3, Vector3};
+use demo_cube_wgpu::draw_context::DrawContext;
+use demo_cube_wgpu::primitives::{cube, InstanceData, Object3DInstance};
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
+use demo_cube_wgpu::scene::Scene3D;
+
+const INSTANCES_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/instances.wgsl"
+));
+
+const GRID_SIZE: usize = 10;
+const GRID_SPACING: f32 = 0.5;
+const CUBE_SCALE: f32 = 0.15;
+const ROTATION_DEG_PER_S: f32 = 45.0;
+
+pub struct MainScenario {
+    pub scene: Scene3D,
+}
+
+impl Scenario for MainScenario {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
+        let shader_module =
+            draw_context.create_shader_module("Instances Shader", INSTANCES_SHADER)?;
+        let vertex_state = wgpu::VertexState {
+            module: &shader_module,
+            entry_point: None,
+            buffers: &[
+                draw_context.vertex_buffer_layout.clone(),
+                InstanceData::vertex_buffer_layout(),
+            ],
+            compilation_options: Default::default(),
+        };
+        let fragment_state = wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: None,
+            targets: &[Some(wgpu::ColorTargetState {
+                format: draw_context.surface_format(),
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        };
+        // Grid of GRID_SIZE^3 cubes, centered on the origin
+        let offset = (GRID_SIZE - 1) as f32 * GRID_SPACING / 2.0;
+        let instances: Vec<Object3DInstance> = (0..GRID_SIZE.pow(3))
+            .map(|index| {
+                let (x, y, z) = (
+                    index % GRID_SIZE,
+                    (index / GRID_SIZE) % GRID_SIZE,
+                    index / (GRID_SIZE * GRID_SIZE),
+                );
+                let mut instance = Object3DInstance::from(Vector3::new(
+                    x as f32 * GRID_SPACING - offset,
+                    y as f32 * GRID_SPACING - offset,
+                    z as f32 * GRID_SPACING - offset,
+                ));
+                instance.set_scale(CUBE_SCALE);
+                instance
+            })
+            .collect();
+        let mut cubes =
+            cube::create_cube_instances(draw_context, vertex_state, fragment_state, &instances);
+        cubes.set_animation(|update_interval, index, instance| {
+            // Each cube gets its own phase so that the grid does not rotate as a single block
+            let angle = ROTATION_DEG_PER_S * update_interval.scenario_elapsed.as_secs_f32()
+                + index as f32 * 10.0;
+            instance.set_rotation(Quaternion::from_axis_angle(
+                Vector3::new(1.0, 1.0, 0.0) / 2.0_f32.sqrt(),
+                Deg(angle),
+            ));
+        });
+        let mut scene = Scene3D::new();
+        scene.add_updatable(cubes);
+        Ok(Self { scene })
+    }
+}
+
+impl DynScenario for MainScenario {
+    fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        self.scene.update(context, update_interval);
+    }
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        self.scene.render(render_pass, render_stats);
+    }
+}
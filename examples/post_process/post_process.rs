@@ -0,0 +1,123 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use demo_cube_wgpu::draw_context::DrawContext;
+use demo_cube_wgpu::post_process::PostProcess;
+use demo_cube_wgpu::primitives::{cube, Object3D};
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
+use winit::keyboard::KeyCode;
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+
+const COLOR_EFFECTS_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/color_effects.wgsl"
+));
+
+const ROTATION_DEG_PER_S: f32 = 45.0;
+const EFFECT_COUNT: u32 = 3;
+const STRENGTH_STEP: f32 = 0.1;
+
+// E cycles between grayscale, sepia and invert, [ and ] change the effect strength
+pub struct MainScenario {
+    pub cube: Object3D,
+    post_process: PostProcess,
+    effect: u32,
+    strength: f32,
+}
+
+impl MainScenario {
+    fn write_params(&self, context: &DrawContext) {
+        self.post_process
+            .set_params(context, [self.effect as f32, self.strength, 0.0, 0.0]);
+    }
+}
+
+impl Scenario for MainScenario {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
+        let default_shader_module =
+            draw_context.create_shader_module("Default Shader", DEFAULT_SHADER)?;
+        let vertex_state = wgpu::VertexState {
+            module: &default_shader_module,
+            entry_point: None,
+            buffers: std::slice::from_ref(&draw_context.vertex_buffer_layout),
+            compilation_options: Default::default(),
+        };
+        let fragment_state = wgpu::FragmentState {
+            module: &default_shader_module,
+            entry_point: None,
+            targets: &[Some(wgpu::ColorTargetState {
+                format: draw_context.surface_format(),
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        };
+        let cube = cube::create_cube(draw_context, vertex_state, fragment_state);
+        let post_process = PostProcess::new(draw_context, COLOR_EFFECTS_SHADER)?;
+        let scenario = Self {
+            cube,
+            post_process,
+            effect: 0,
+            strength: 1.0,
+        };
+        scenario.write_params(draw_context);
+        Ok(scenario)
+    }
+}
+
+impl DynScenario for MainScenario {
+    fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        let input = &update_interval.input;
+        if input.just_pressed(KeyCode::KeyE) {
+            self.effect = (self.effect + 1) % EFFECT_COUNT;
+        }
+        if input.just_pressed(KeyCode::BracketLeft) {
+            self.strength = (self.strength - STRENGTH_STEP).max(0.0);
+        }
+        if input.just_pressed(KeyCode::BracketRight) {
+            self.strength = (self.strength + STRENGTH_STEP).min(1.0);
+        }
+        self.write_params(context);
+        let delta_rotation = ROTATION_DEG_PER_S * update_interval.update_delta.as_secs_f32();
+        self.cube.apply_transform(
+            context,
+            cgmath::Matrix4::from_angle_y(cgmath::Deg(delta_rotation)),
+        );
+    }
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        self.cube.as_ref().render(render_pass);
+        render_stats.record_draw();
+    }
+    fn post_process(&self) -> Option<&PostProcess> {
+        Some(&self.post_process)
+    }
+}
@@ -22,9 +22,9 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use demo_cube_wgpu::draw_context::DrawContext;
+use demo_cube_wgpu::draw_context::{BlendMode, DrawContext};
 use demo_cube_wgpu::primitives::{cube, Object3D};
-use demo_cube_wgpu::scenario::{Scenario, UpdateInterval};
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
 
 use web_time::Duration;
 
@@ -47,57 +47,38 @@ pub struct MainScenario {
 }
 
 impl Scenario for MainScenario {
-    fn new(draw_context: &DrawContext) -> Self {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
         let default_shader_module =
-            draw_context
-                .device
-                .create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some("Vertex Shader"),
-                    source: wgpu::ShaderSource::Wgsl(DEFAULT_SHADER.into()),
-                });
+            draw_context.create_shader_module("Vertex Shader", DEFAULT_SHADER)?;
         let default_vertex_state = wgpu::VertexState {
             module: &default_shader_module,
             entry_point: None,
-            buffers: &[draw_context.vertex_buffer_layout.clone()],
+            buffers: std::slice::from_ref(&draw_context.vertex_buffer_layout),
             compilation_options: Default::default(),
         };
         let default_fragment_state = wgpu::FragmentState {
             module: &default_shader_module,
             entry_point: None,
             targets: &[Some(wgpu::ColorTargetState {
-                format: draw_context.surface_config.format,
+                format: draw_context.surface_format(),
                 blend: None,
                 write_mask: wgpu::ColorWrites::ALL,
             })],
             compilation_options: Default::default(),
         };
-        let flat_shader_module =
-            draw_context
-                .device
-                .create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some("Vertex Shader"),
-                    source: wgpu::ShaderSource::Wgsl(FLAT_SHADER.into()),
-                });
+        let flat_shader_module = draw_context.create_shader_module("Vertex Shader", FLAT_SHADER)?;
         let flat_vertex_state = wgpu::VertexState {
             module: &flat_shader_module,
             entry_point: None,
-            buffers: &[draw_context.vertex_buffer_layout.clone()],
+            buffers: std::slice::from_ref(&draw_context.vertex_buffer_layout),
             compilation_options: Default::default(),
         };
-        let blend_state = wgpu::BlendState {
-            color: wgpu::BlendComponent {
-                src_factor: wgpu::BlendFactor::Constant,
-                dst_factor: wgpu::BlendFactor::OneMinusConstant,
-                operation: wgpu::BlendOperation::Add,
-            },
-            alpha: Default::default(),
-        };
         let flat_fragment_state = wgpu::FragmentState {
             module: &flat_shader_module,
             entry_point: None,
             targets: &[Some(wgpu::ColorTargetState {
-                format: draw_context.surface_config.format,
-                blend: Some(blend_state),
+                format: draw_context.surface_format(),
+                blend: Some(BlendMode::ConstantOpacity.blend_state()),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
             compilation_options: Default::default(),
@@ -105,11 +86,14 @@ impl Scenario for MainScenario {
         let cube_interpolated =
             cube::create_cube(draw_context, default_vertex_state, default_fragment_state);
         let cube_flat = cube::create_cube(draw_context, flat_vertex_state, flat_fragment_state);
-        Self {
+        Ok(Self {
             cube_interpolated,
             cube_flat,
-        }
+        })
     }
+}
+
+impl DynScenario for MainScenario {
     fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
         let delta_rotation = ROTATION_DEG_PER_S * update_interval.update_delta.as_secs_f32();
         let transform = cgmath::Matrix4::from_angle_z(cgmath::Deg(delta_rotation))
@@ -117,17 +101,21 @@ impl Scenario for MainScenario {
         self.cube_interpolated.apply_transform(context, transform);
         self.cube_flat.apply_transform(context, transform);
         self.cube_flat.set_opacity(
+            context,
             0.5 + f32::sin(
-                2. * update_interval.scenario_start.elapsed().as_secs_f32()
+                2. * update_interval.scenario_elapsed.as_secs_f32()
                     / SHADER_TRANSITION_PERIOD.as_secs_f32(),
             ) / 2_f32,
         );
     }
-    fn render<'drawable, 'render>(
+    fn render<'drawable>(
         &'drawable self,
-        render_pass: &'render mut wgpu::RenderPass<'drawable>,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
     ) {
         self.cube_interpolated.as_ref().render(render_pass);
         self.cube_flat.as_ref().render(render_pass);
+        render_stats.record_draw();
+        render_stats.record_draw();
     }
 }
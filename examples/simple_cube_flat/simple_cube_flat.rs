@@ -24,7 +24,7 @@ SOFTWARE.
 
 use demo_cube_wgpu::draw_context::DrawContext;
 use demo_cube_wgpu::primitives::{cube, Object3D};
-use demo_cube_wgpu::scenario::{Scenario, UpdateInterval};
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
 
 const DEFAULT_SHADER: &str = include_str!(concat!(
     env!("CARGO_MANIFEST_DIR"),
@@ -38,35 +38,33 @@ pub struct MainScenario {
 }
 
 impl Scenario for MainScenario {
-    fn new(draw_context: &DrawContext) -> Self {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
         let default_shader_module =
-            draw_context
-                .device
-                .create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some("Vertex Shader"),
-                    source: wgpu::ShaderSource::Wgsl(DEFAULT_SHADER.into()),
-                });
+            draw_context.create_shader_module("Vertex Shader", DEFAULT_SHADER)?;
         let vertex_state = wgpu::VertexState {
             module: &default_shader_module,
             entry_point: None,
-            buffers: &[draw_context.vertex_buffer_layout.clone()],
+            buffers: std::slice::from_ref(&draw_context.vertex_buffer_layout),
             compilation_options: Default::default(),
         };
         let fragment_state = wgpu::FragmentState {
             module: &default_shader_module,
             entry_point: None,
             targets: &[Some(wgpu::ColorTargetState {
-                format: draw_context.surface_config.format,
+                format: draw_context.surface_format(),
                 blend: Some(wgpu::BlendState::REPLACE),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
             compilation_options: Default::default(),
         };
         let cube = cube::create_cube(draw_context, vertex_state, fragment_state);
-        Self { cube }
+        Ok(Self { cube })
     }
+}
+
+impl DynScenario for MainScenario {
     fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
-        let total_seconds = update_interval.scenario_start.elapsed().as_secs_f32();
+        let total_seconds = update_interval.scenario_elapsed.as_secs_f32();
         let new_rotation = ROTATION_DEG_PER_S * total_seconds;
         // Translation on z to be in the clipped space (between -w and w) and camera in front of the cube
         let z_translation: cgmath::Matrix4<f32> =
@@ -75,10 +73,12 @@ impl Scenario for MainScenario {
             cgmath::Matrix4::from_angle_z(cgmath::Deg(new_rotation));
         self.cube.set_transform(context, transform * z_translation);
     }
-    fn render<'drawable, 'render>(
+    fn render<'drawable>(
         &'drawable self,
-        render_pass: &'render mut wgpu::RenderPass<'drawable>,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
     ) {
         self.cube.as_ref().render(render_pass);
+        render_stats.record_draw();
     }
 }
@@ -0,0 +1,111 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use demo_cube_wgpu::draw_context::{DrawContext, NORMALS_DEBUG_SHADER};
+use demo_cube_wgpu::lights::{Light, LAMBERT_SHADER};
+use demo_cube_wgpu::primitives::{cube, Object3D};
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
+use log::error;
+use std::rc::Rc;
+use winit::keyboard::KeyCode;
+
+const ROTATION_DEG_PER_S: f32 = 45.0;
+const NORMALS_DEBUG_KEY: KeyCode = KeyCode::KeyN;
+
+// The N key switches between the lit cube and its normals
+pub struct MainScenario {
+    pub cube: Object3D,
+    lambert_shader_module: Rc<wgpu::ShaderModule>,
+    normals_debug_shader_module: Rc<wgpu::ShaderModule>,
+    normals_debug: bool,
+}
+
+impl Scenario for MainScenario {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
+        let lambert_shader_module =
+            draw_context.create_shader_module("Lambert Shader", LAMBERT_SHADER)?;
+        let vertex_state = wgpu::VertexState {
+            module: &lambert_shader_module,
+            entry_point: None,
+            buffers: std::slice::from_ref(&draw_context.normal_vertex_buffer_layout),
+            compilation_options: Default::default(),
+        };
+        let fragment_state = wgpu::FragmentState {
+            module: &lambert_shader_module,
+            entry_point: None,
+            targets: &[Some(wgpu::ColorTargetState {
+                format: draw_context.surface_format(),
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        };
+        let cube = cube::create_cube_with_normals(draw_context, vertex_state, fragment_state);
+        draw_context.set_light(&Light {
+            direction: [1.0, -1.0, 1.0],
+            ..Default::default()
+        });
+        let normals_debug_shader_module =
+            draw_context.create_shader_module("Normals Debug Shader", NORMALS_DEBUG_SHADER)?;
+        Ok(Self {
+            cube,
+            lambert_shader_module,
+            normals_debug_shader_module,
+            normals_debug: false,
+        })
+    }
+}
+
+impl DynScenario for MainScenario {
+    fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        if update_interval.input.just_pressed(NORMALS_DEBUG_KEY) {
+            self.normals_debug = !self.normals_debug;
+            let shader_module = match self.normals_debug {
+                true => &self.normals_debug_shader_module,
+                false => &self.lambert_shader_module,
+            };
+            if let Err(err) =
+                self.cube
+                    .as_mut()
+                    .replace_shaders(context, shader_module, shader_module)
+            {
+                error!("Could not switch the shaders: {err:#}");
+            }
+        }
+        let total_seconds = update_interval.scenario_elapsed.as_secs_f32();
+        let new_rotation = ROTATION_DEG_PER_S * total_seconds;
+        let transform: cgmath::Matrix4<f32> =
+            cgmath::Matrix4::from_angle_y(cgmath::Deg(new_rotation))
+                * cgmath::Matrix4::from_angle_x(cgmath::Deg(new_rotation / 2.));
+        self.cube.set_transform(context, transform);
+    }
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        self.cube.as_ref().render(render_pass);
+        render_stats.record_draw();
+    }
+}
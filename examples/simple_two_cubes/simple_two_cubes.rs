@@ -23,8 +23,9 @@ SOFTWARE.
 */
 
 use demo_cube_wgpu::draw_context::DrawContext;
-use demo_cube_wgpu::primitives::{cube, Object3D};
-use demo_cube_wgpu::scenario::{Scenario, UpdateInterval};
+use demo_cube_wgpu::primitives::cube;
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
+use demo_cube_wgpu::scene::Scene3D;
 
 const DEFAULT_SHADER: &str = include_str!(concat!(
     env!("CARGO_MANIFEST_DIR"),
@@ -39,53 +40,41 @@ const FLAT_SHADER: &str = include_str!(concat!(
 const ROTATION_DEG_PER_S: f32 = 45.0;
 
 pub struct MainScenario {
-    pub cube_left: Object3D,
-    pub cube_right: Object3D,
+    pub scene: Scene3D,
 }
 
 impl Scenario for MainScenario {
-    fn new(draw_context: &DrawContext) -> Self {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
         let default_shader_module =
-            draw_context
-                .device
-                .create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some("Vertex Shader"),
-                    source: wgpu::ShaderSource::Wgsl(DEFAULT_SHADER.into()),
-                });
+            draw_context.create_shader_module("Vertex Shader", DEFAULT_SHADER)?;
         let default_vertex_state = wgpu::VertexState {
             module: &default_shader_module,
             entry_point: None,
-            buffers: &[draw_context.vertex_buffer_layout.clone()],
+            buffers: std::slice::from_ref(&draw_context.vertex_buffer_layout),
             compilation_options: Default::default(),
         };
         let default_fragment_state = wgpu::FragmentState {
             module: &default_shader_module,
             entry_point: None,
             targets: &[Some(wgpu::ColorTargetState {
-                format: draw_context.surface_config.format,
+                format: draw_context.surface_format(),
                 blend: Some(wgpu::BlendState::REPLACE),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
             compilation_options: Default::default(),
         };
-        let flat_shader_module =
-            draw_context
-                .device
-                .create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some("Vertex Shader"),
-                    source: wgpu::ShaderSource::Wgsl(FLAT_SHADER.into()),
-                });
+        let flat_shader_module = draw_context.create_shader_module("Vertex Shader", FLAT_SHADER)?;
         let flat_vertex_state = wgpu::VertexState {
             module: &flat_shader_module,
             entry_point: None,
-            buffers: &[draw_context.vertex_buffer_layout.clone()],
+            buffers: std::slice::from_ref(&draw_context.vertex_buffer_layout),
             compilation_options: Default::default(),
         };
         let flat_fragment_state = wgpu::FragmentState {
             module: &flat_shader_module,
             entry_point: None,
             targets: &[Some(wgpu::ColorTargetState {
-                format: draw_context.surface_config.format,
+                format: draw_context.surface_format(),
                 blend: Some(wgpu::BlendState::REPLACE),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
@@ -103,27 +92,34 @@ impl Scenario for MainScenario {
             draw_context,
             cgmath::Matrix4::from_translation(cgmath::Vector3::new(0.5, 0.0, 0.0)),
         );
-        Self {
-            cube_left,
-            cube_right,
-        }
+        let mut scene = Scene3D::new();
+        scene.add_animated(cube_right, |context, update_interval, cube| {
+            let delta_rotation = ROTATION_DEG_PER_S * update_interval.update_delta.as_secs_f32();
+            cube.apply_transform(
+                context,
+                cgmath::Matrix4::from_angle_y(cgmath::Deg(delta_rotation)),
+            );
+        });
+        scene.add_animated(cube_left, |context, update_interval, cube| {
+            let delta_rotation = ROTATION_DEG_PER_S * update_interval.update_delta.as_secs_f32();
+            cube.apply_transform(
+                context,
+                cgmath::Matrix4::from_angle_z(cgmath::Deg(delta_rotation)),
+            );
+        });
+        Ok(Self { scene })
     }
+}
+
+impl DynScenario for MainScenario {
     fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
-        let delta_rotation = ROTATION_DEG_PER_S * update_interval.update_delta.as_secs_f32();
-        self.cube_left.apply_transform(
-            context,
-            cgmath::Matrix4::from_angle_z(cgmath::Deg(delta_rotation)),
-        );
-        self.cube_right.apply_transform(
-            context,
-            cgmath::Matrix4::from_angle_y(cgmath::Deg(delta_rotation)),
-        );
+        self.scene.update(context, update_interval);
     }
-    fn render<'drawable, 'render>(
+    fn render<'drawable>(
         &'drawable self,
-        render_pass: &'render mut wgpu::RenderPass<'drawable>,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
     ) {
-        self.cube_right.as_ref().render(render_pass);
-        self.cube_left.as_ref().render(render_pass);
+        self.scene.render(render_pass, render_stats);
     }
 }
@@ -0,0 +1,130 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::Point3;
+use demo_cube_wgpu::cameras::{Camera, OrthogonalConfig, PerspectiveConfig, WinitCameraAdapter};
+use demo_cube_wgpu::draw_context::{DrawContext, Viewport, ViewportRect};
+use demo_cube_wgpu::primitives::{cube, Object3D};
+use demo_cube_wgpu::scenario::{DynScenario, RenderStats, Scenario, UpdateInterval};
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+
+const ROTATION_DEG_PER_S: f32 = 45.0;
+
+const LEFT_HALF: ViewportRect = ViewportRect {
+    x: 0.,
+    y: 0.,
+    width: 0.5,
+    height: 1.,
+};
+
+const RIGHT_HALF: ViewportRect = ViewportRect {
+    x: 0.5,
+    y: 0.,
+    width: 0.5,
+    height: 1.,
+};
+
+// The cube seen by a perspective camera on the left, moved with the input, and by a fixed
+// orthogonal camera on the right
+pub struct MainScenario {
+    pub cube: Object3D,
+    perspective_camera: WinitCameraAdapter,
+    ortho_camera: Camera,
+    viewports: Vec<Viewport>,
+}
+
+impl Scenario for MainScenario {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
+        let default_shader_module =
+            draw_context.create_shader_module("Fragment Shader", DEFAULT_SHADER)?;
+        let vertex_state = wgpu::VertexState {
+            module: &default_shader_module,
+            entry_point: None,
+            buffers: std::slice::from_ref(&draw_context.vertex_buffer_layout),
+            compilation_options: Default::default(),
+        };
+        let fragment_state = wgpu::FragmentState {
+            module: &default_shader_module,
+            entry_point: None,
+            targets: &[Some(wgpu::ColorTargetState {
+                format: draw_context.surface_format(),
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        };
+        let cube = cube::create_cube(draw_context, vertex_state, fragment_state);
+        let perspective_camera = WinitCameraAdapter::new(Camera::from(PerspectiveConfig {
+            ..Default::default()
+        }));
+        let ortho_camera = Camera::from(OrthogonalConfig {
+            eye: Point3::new(5., 5., -5.),
+            ..Default::default()
+        });
+        Ok(Self {
+            cube,
+            perspective_camera,
+            ortho_camera,
+            viewports: Vec::new(),
+        })
+    }
+}
+
+impl DynScenario for MainScenario {
+    fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        let total_seconds = update_interval.scenario_elapsed.as_secs_f32();
+        let transform: cgmath::Matrix4<f32> =
+            cgmath::Matrix4::from_angle_y(cgmath::Deg(ROTATION_DEG_PER_S * total_seconds));
+        self.cube.set_transform(context, transform);
+        // Each camera only sees half of the window
+        let half_aspect_ratio = context.aspect_ratio() / 2.;
+        self.perspective_camera
+            .as_mut()
+            .set_aspect(half_aspect_ratio);
+        self.ortho_camera.set_aspect(half_aspect_ratio);
+        self.ortho_camera.set_reverse_z(context.is_reverse_z());
+        self.viewports = vec![
+            Viewport::new(self.perspective_camera.get_camera_matrix(), LEFT_HALF),
+            Viewport::new(self.ortho_camera.get_camera_matrix(), RIGHT_HALF),
+        ];
+    }
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        self.cube.as_ref().render(render_pass);
+        render_stats.record_draw();
+    }
+    fn active_camera(&mut self) -> Option<&mut WinitCameraAdapter> {
+        Some(&mut self.perspective_camera)
+    }
+    fn viewports(&self) -> Option<&[Viewport]> {
+        Some(&self.viewports)
+    }
+}
@@ -22,7 +22,9 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use cgmath::{vec3, Matrix4, PerspectiveFov, Rad, Vector3};
+use cgmath::{
+    vec3, vec4, EuclideanSpace, InnerSpace, Matrix4, PerspectiveFov, Rad, SquareMatrix, Vector3,
+};
 use cgmath::{Ortho, Point3};
 use log::{debug, warn};
 use std::collections::BTreeSet;
@@ -31,11 +33,18 @@ use std::sync::LazyLock;
 use winit::event::{DeviceEvent, ElementState, KeyEvent};
 use winit::keyboard::{KeyCode, PhysicalKey};
 
+use crate::frustum::{Frustum, Plane};
+use crate::primitives::Bounds;
+
 static SWITCH_Z_AXIS: LazyLock<Matrix4<f32>> =
     LazyLock::new(|| Matrix4::from_nonuniform_scale(1., 1., -1.));
 static TO_WEBGPU_NDCS: LazyLock<Matrix4<f32>> = LazyLock::new(|| {
     Matrix4::from_translation(vec3(0., 0., 0.5)) * Matrix4::from_nonuniform_scale(1., 1., 0.5)
 });
+// Near plane mapped to 1 and far plane to 0, to be used with DrawContextConfig::reverse_z
+static TO_WEBGPU_NDCS_REVERSE_Z: LazyLock<Matrix4<f32>> = LazyLock::new(|| {
+    Matrix4::from_translation(vec3(0., 0., 0.5)) * Matrix4::from_nonuniform_scale(1., 1., -0.5)
+});
 
 pub struct OrthogonalConfig {
     pub width: f32,
@@ -75,8 +84,8 @@ impl Default for OrthogonalConfig {
 
 impl From<OrthogonalConfig> for Camera {
     fn from(config: OrthogonalConfig) -> Self {
-        Camera {
-            projection: Matrix4::from(Ortho {
+        Camera::new(
+            Lens::Orthogonal(Ortho {
                 left: -config.width / 2.0,
                 right: config.width / 2.0,
                 bottom: -config.height / 2.0,
@@ -84,8 +93,8 @@ impl From<OrthogonalConfig> for Camera {
                 near: config.near,
                 far: config.far,
             }),
-            view: Matrix4::look_at_lh(config.eye, config.center, config.up),
-        }
+            Matrix4::look_at_lh(config.eye, config.center, config.up),
+        )
     }
 }
 
@@ -127,27 +136,351 @@ impl Default for PerspectiveConfig {
 
 impl From<PerspectiveConfig> for Camera {
     fn from(config: PerspectiveConfig) -> Self {
-        Camera {
-            projection: Matrix4::from(PerspectiveFov {
+        Camera::new(
+            Lens::Perspective(PerspectiveFov {
                 fovy: Rad(config.fovy),
                 aspect: config.aspect,
                 near: config.near,
                 far: config.far,
             }),
-            view: Matrix4::look_at_lh(config.eye, config.center, config.up),
+            Matrix4::look_at_lh(config.eye, config.center, config.up),
+        )
+    }
+}
+
+// Snapshot of a camera, to be restored with Camera::set_pose
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraPose {
+    pub view: [[f32; 4]; 4],
+    pub lens: LensPose,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LensPose {
+    Perspective {
+        fovy: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    },
+    Orthogonal {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProjectionMode {
+    Perspective,
+    Orthogonal,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Lens {
+    Perspective(PerspectiveFov<f32>),
+    Orthogonal(Ortho<f32>),
+}
+
+impl Lens {
+    fn projection(&self) -> Matrix4<f32> {
+        match *self {
+            Lens::Perspective(perspective) => Matrix4::from(perspective),
+            Lens::Orthogonal(ortho) => Matrix4::from(ortho),
+        }
+    }
+
+    fn pose(&self) -> LensPose {
+        match *self {
+            Lens::Perspective(perspective) => LensPose::Perspective {
+                fovy: perspective.fovy.0,
+                aspect: perspective.aspect,
+                near: perspective.near,
+                far: perspective.far,
+            },
+            Lens::Orthogonal(ortho) => LensPose::Orthogonal {
+                left: ortho.left,
+                right: ortho.right,
+                bottom: ortho.bottom,
+                top: ortho.top,
+                near: ortho.near,
+                far: ortho.far,
+            },
+        }
+    }
+
+    fn from_pose(pose: LensPose) -> Self {
+        match pose {
+            LensPose::Perspective {
+                fovy,
+                aspect,
+                near,
+                far,
+            } => Lens::Perspective(PerspectiveFov {
+                fovy: Rad(fovy),
+                aspect,
+                near,
+                far,
+            }),
+            LensPose::Orthogonal {
+                left,
+                right,
+                bottom,
+                top,
+                near,
+                far,
+            } => Lens::Orthogonal(Ortho {
+                left,
+                right,
+                bottom,
+                top,
+                near,
+                far,
+            }),
         }
     }
 }
 
 #[derive(Debug)]
 pub struct Camera {
-    pub projection: Matrix4<f32>,
     pub view: Matrix4<f32>,
+    lens: Lens,
+    reverse_z: bool,
 }
 
 impl Camera {
+    const PIXEL_ORTHO_NEAR: f32 = -1.0;
+    const PIXEL_ORTHO_FAR: f32 = 1.0;
+    const SWITCH_PERSPECTIVE_NEAR: f32 = 0.1;
+    const MIN_SWITCH_FOVY: f32 = PI / 180.;
+    const MAX_SWITCH_FOVY: f32 = PI * 170. / 180.;
+    const MIN_FRAME_RADIUS: f32 = 0.001;
+    const MIN_FRAME_NEAR_RATIO: f32 = 0.001;
+
+    fn new(lens: Lens, view: Matrix4<f32>) -> Self {
+        Camera {
+            view,
+            lens,
+            reverse_z: false,
+        }
+    }
+
+    // Lens setters leave the view untouched
+    pub fn set_fovy(&mut self, fovy: f32) {
+        let Lens::Perspective(ref mut perspective) = self.lens else {
+            warn!("Field of view can only be set on a perspective camera");
+            return;
+        };
+        perspective.fovy = Rad(fovy);
+    }
+
+    pub fn set_near_far(&mut self, near: f32, far: f32) {
+        match self.lens {
+            Lens::Perspective(ref mut perspective) => {
+                perspective.near = near;
+                perspective.far = far;
+            }
+            Lens::Orthogonal(ref mut ortho) => {
+                ortho.near = near;
+                ortho.far = far;
+            }
+        }
+    }
+
+    pub fn get_near_far(&self) -> (f32, f32) {
+        match self.lens {
+            Lens::Perspective(ref perspective) => (perspective.near, perspective.far),
+            Lens::Orthogonal(ref ortho) => (ortho.near, ortho.far),
+        }
+    }
+
+    pub fn get_projection_mode(&self) -> ProjectionMode {
+        match self.lens {
+            Lens::Perspective(_) => ProjectionMode::Perspective,
+            Lens::Orthogonal(_) => ProjectionMode::Orthogonal,
+        }
+    }
+
+    // Only the projection changes. To keep the framing, the orthogonal extent is the size of the
+    // perspective frustum section at focus_distance, height = 2 * distance * tan(fovy / 2), and the
+    // reverse conversion gives back the field of view. Objects at that distance keep their size on
+    // screen, nearer ones look smaller in orthogonal and farther ones bigger.
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode, focus_distance: f32) {
+        let focus_distance = focus_distance.max(f32::EPSILON);
+        self.lens = match (self.lens, mode) {
+            (Lens::Perspective(perspective), ProjectionMode::Orthogonal) => {
+                let height = 2. * focus_distance * (perspective.fovy.0 / 2.).tan();
+                let width = height * perspective.aspect;
+                Lens::Orthogonal(Ortho {
+                    left: -width / 2.,
+                    right: width / 2.,
+                    bottom: -height / 2.,
+                    top: height / 2.,
+                    near: perspective.near,
+                    far: perspective.far,
+                })
+            }
+            (Lens::Orthogonal(ortho), ProjectionMode::Perspective) => {
+                let height = (ortho.top - ortho.bottom).abs();
+                let fovy = 2. * (height / 2. / focus_distance).atan();
+                Lens::Perspective(PerspectiveFov {
+                    fovy: Rad(fovy.clamp(Self::MIN_SWITCH_FOVY, Self::MAX_SWITCH_FOVY)),
+                    aspect: (ortho.right - ortho.left).abs() / height,
+                    // A perspective projection needs a near plane in front of the camera
+                    near: match ortho.near > 0. {
+                        true => ortho.near,
+                        false => Self::SWITCH_PERSPECTIVE_NEAR,
+                    },
+                    far: ortho.far,
+                })
+            }
+            (lens, _) => lens,
+        };
+    }
+
+    // Moves the eye backward along the current viewing direction, so that the bounding sphere of
+    // the bounds fills the view and its center is at the middle. For an orthogonal camera the
+    // extent becomes the sphere diameter on the smallest side. Near and far planes are set around
+    // the sphere.
+    pub fn frame(&mut self, bounds: &Bounds) {
+        let center = bounds.center();
+        let radius = bounds.radius().max(Self::MIN_FRAME_RADIUS);
+        let forward = self.forward().normalize();
+        let up = vec3(self.view.x.y, self.view.y.y, self.view.z.y);
+        let distance = match self.lens {
+            Lens::Perspective(ref mut perspective) => {
+                let half_fovy = perspective.fovy.0 / 2.;
+                let half_fovx = (half_fovy.tan() * perspective.aspect).atan();
+                let distance = radius / half_fovy.min(half_fovx).sin();
+                perspective.near = (distance - radius).max(distance * Self::MIN_FRAME_NEAR_RATIO);
+                perspective.far = distance + radius;
+                distance
+            }
+            Lens::Orthogonal(ref mut ortho) => {
+                let aspect = (ortho.right - ortho.left).abs() / (ortho.top - ortho.bottom).abs();
+                let half_height = radius * (1. / aspect).max(1.);
+                let half_width = half_height * aspect;
+                (ortho.left, ortho.right) = (-half_width, half_width);
+                // Kept upside down for a pixel camera
+                (ortho.bottom, ortho.top) = match ortho.top > ortho.bottom {
+                    true => (-half_height, half_height),
+                    false => (half_height, -half_height),
+                };
+                // Any distance works, the eye is just kept out of the sphere
+                let distance = 2. * radius;
+                ortho.near = distance - radius;
+                ortho.far = distance + radius;
+                distance
+            }
+        };
+        self.look_at(center - forward * distance, center, up);
+    }
+
+    // For an orthogonal camera, the height is kept and the width adapted
+    pub fn set_aspect(&mut self, aspect: f32) {
+        match self.lens {
+            Lens::Perspective(ref mut perspective) => perspective.aspect = aspect,
+            Lens::Orthogonal(ortho) => {
+                let height = (ortho.top - ortho.bottom).abs();
+                self.set_extent(height * aspect, height);
+            }
+        }
+    }
+
+    // Bounds are scaled relatively to the origin, so a centered camera stays centered and a
+    // pixel camera keeps its top-left origin
+    pub fn set_extent(&mut self, width: f32, height: f32) {
+        let Lens::Orthogonal(ref mut ortho) = self.lens else {
+            warn!("Extent can only be set on an orthogonal camera");
+            return;
+        };
+        let width_ratio = width / (ortho.right - ortho.left).abs();
+        let height_ratio = height / (ortho.top - ortho.bottom).abs();
+        ortho.left *= width_ratio;
+        ortho.right *= width_ratio;
+        ortho.bottom *= height_ratio;
+        ortho.top *= height_ratio;
+    }
+
+    // Screen space camera for HUD and 2D sprites: (0, 0) is the top-left corner and
+    // (width, height) the bottom-right one, in pixels. Drawables with z in [-1, 1] are visible,
+    // lower z being in front, so z = 0 is a safe default. As the Y axis points down, triangles
+    // must be wound the opposite way compared to the 3D cameras to not be culled.
+    pub fn pixel_ortho(width: f32, height: f32) -> Self {
+        Camera::new(
+            Lens::Orthogonal(Ortho {
+                left: 0.,
+                right: width,
+                bottom: height,
+                top: 0.,
+                near: Self::PIXEL_ORTHO_NEAR,
+                far: Self::PIXEL_ORTHO_FAR,
+            }),
+            Matrix4::identity(),
+        )
+    }
+
+    // Must match DrawContext::is_reverse_z, or everything fails the depth test
+    pub fn set_reverse_z(&mut self, reverse_z: bool) {
+        self.reverse_z = reverse_z;
+    }
+
+    // Derived from the lens, so it cannot get out of sync with it
+    pub fn projection(&self) -> Matrix4<f32> {
+        self.lens.projection()
+    }
     pub fn get_camera_matrix(&self) -> Matrix4<f32> {
-        (*TO_WEBGPU_NDCS) * self.projection * (*SWITCH_Z_AXIS) * self.view
+        let to_webgpu_ndcs = match self.reverse_z {
+            true => *TO_WEBGPU_NDCS_REVERSE_Z,
+            false => *TO_WEBGPU_NDCS,
+        };
+        to_webgpu_ndcs * self.projection() * (*SWITCH_Z_AXIS) * self.view
+    }
+    // Camera matrix without the translation of the view, for what must stay around the camera
+    // whatever its position, like a skybox
+    pub fn get_rotation_camera_matrix(&self) -> Matrix4<f32> {
+        let to_webgpu_ndcs = match self.reverse_z {
+            true => *TO_WEBGPU_NDCS_REVERSE_Z,
+            false => *TO_WEBGPU_NDCS,
+        };
+        let mut rotation = self.view;
+        rotation.w = vec4(0., 0., 0., 1.);
+        to_webgpu_ndcs * self.projection() * (*SWITCH_Z_AXIS) * rotation
+    }
+    // Left, right, bottom, top, near and far planes, in world coordinates, see Frustum
+    pub fn frustum_planes(&self) -> [Plane; 6] {
+        let mut planes = *Frustum::from_camera_matrix(&self.get_camera_matrix()).planes();
+        if self.reverse_z {
+            planes.swap(4, 5);
+        }
+        planes
+    }
+    pub fn eye(&self) -> Point3<f32> {
+        let inverse_view = self.view.invert().unwrap_or_else(Matrix4::identity);
+        Point3::from_vec(inverse_view.w.truncate())
+    }
+    // Rebuilds the view from scratch, so no roll accumulated by rotations is kept
+    pub fn look_at(&mut self, eye: Point3<f32>, target: Point3<f32>, up: Vector3<f32>) {
+        self.view = Matrix4::look_at_lh(eye, target, up);
+    }
+    // Direction the camera looks at, in world coordinates
+    pub fn forward(&self) -> Vector3<f32> {
+        vec3(self.view.x.z, self.view.y.z, self.view.z.z)
+    }
+    pub fn pose(&self) -> CameraPose {
+        CameraPose {
+            view: self.view.into(),
+            lens: self.lens.pose(),
+        }
+    }
+    pub fn set_pose(&mut self, pose: CameraPose) {
+        self.view = pose.view.into();
+        self.lens = Lens::from_pose(pose.lens);
     }
     fn move_z(&mut self, val: f32) {
         self.view = Matrix4::from_translation(Vector3::new(0., 0., -val)) * self.view;
@@ -170,35 +503,228 @@ impl Camera {
     }
 }
 
+#[derive(Default)]
+struct GamepadSticks {
+    move_x: f32,
+    move_z: f32,
+    look_x: f32,
+    look_y: f32,
+}
+
+impl GamepadSticks {
+    fn is_idle(&self) -> bool {
+        self.move_x == 0. && self.move_z == 0. && self.look_x == 0. && self.look_y == 0.
+    }
+}
+
+// With Free, rotations are composed with the current orientation, which lets the horizon roll
+// over time. WorldUp recomputes the yaw and pitch and keeps the camera up vector world-up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RotationMode {
+    #[default]
+    Free,
+    WorldUp,
+}
+
 pub struct WinitCameraAdapter {
     camera: Camera,
+    rotation_mode: RotationMode,
     enabled_keys: BTreeSet<KeyCode>,
     key_speed: f32,
     rotation_speed: f32,
+    // Factors applied to the rotation speed, per axis
+    sensitivity_x: f32,
+    sensitivity_y: f32,
+    invert_y: bool,
+    gamepad_sticks: GamepadSticks,
+    gamepad_deadzone: f32,
+    gamepad_sensitivity: f32,
+    // Factor applied to the distance to the origin, and minimal far plane
+    auto_far: Option<(f32, f32)>,
 }
 
 impl WinitCameraAdapter {
     const DEFAULT_KEY_SPEED: f32 = 0.03;
     const DEFAULT_ROTATION_SPEED: f32 = 1.0 / 500.0;
+    const DEFAULT_SENSITIVITY: f32 = 1.0;
+    const DEFAULT_GAMEPAD_DEADZONE: f32 = 0.15;
+    const DEFAULT_GAMEPAD_SENSITIVITY: f32 = 1.0;
+    // Below 1, as the stick values are rescaled by the remaining range
+    const MAX_GAMEPAD_DEADZONE: f32 = 0.95;
+    // Rotation applied each frame for a fully tilted stick, before sensitivity
+    const GAMEPAD_LOOK_SPEED: f32 = 0.03;
+    // Looking straight up or down would make the world-up look_at degenerate
+    const MAX_PITCH: f32 = PI / 2. - 0.01;
 
     pub fn new(camera: Camera) -> Self {
         WinitCameraAdapter {
             camera,
+            rotation_mode: RotationMode::default(),
             enabled_keys: BTreeSet::new(),
             key_speed: Self::DEFAULT_KEY_SPEED,
             rotation_speed: Self::DEFAULT_ROTATION_SPEED,
+            sensitivity_x: Self::DEFAULT_SENSITIVITY,
+            sensitivity_y: Self::DEFAULT_SENSITIVITY,
+            invert_y: false,
+            gamepad_sticks: GamepadSticks::default(),
+            gamepad_deadzone: Self::DEFAULT_GAMEPAD_DEADZONE,
+            gamepad_sensitivity: Self::DEFAULT_GAMEPAD_SENSITIVITY,
+            auto_far: None,
         }
     }
 
+    // The framing is kept for objects around the origin, see Camera::set_projection_mode
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) {
+        let focus_distance = self.camera.eye().to_vec().magnitude();
+        self.camera.set_projection_mode(mode, focus_distance);
+    }
+
+    // See Camera::frame, e.g. after loading a model of unknown size. The far plane is then only
+    // adjusted if set_auto_far requires a farther one.
+    pub fn frame(&mut self, bounds: &Bounds) {
+        self.camera.frame(bounds);
+        self.adjust_far_plane();
+    }
+
+    pub fn toggle_projection_mode(&mut self) {
+        let mode = match self.camera.get_projection_mode() {
+            ProjectionMode::Perspective => ProjectionMode::Orthogonal,
+            ProjectionMode::Orthogonal => ProjectionMode::Perspective,
+        };
+        self.set_projection_mode(mode);
+    }
+
+    // With a factor, the far plane follows the distance of the camera to the origin, so a scene
+    // around the origin does not get clipped when zooming out. The far plane at the time of the
+    // call is kept as a minimum. None stops the adjustment and leaves the current far plane.
+    pub fn set_auto_far(&mut self, factor: Option<f32>) {
+        let (_, far) = self.camera.get_near_far();
+        self.auto_far = factor.map(|factor| (factor, far));
+        self.adjust_far_plane();
+    }
+
+    fn adjust_far_plane(&mut self) {
+        let Some((factor, min_far)) = self.auto_far else {
+            return;
+        };
+        let (near, far) = self.camera.get_near_far();
+        let auto_far = (self.camera.eye().to_vec().magnitude() * factor).max(min_far);
+        if auto_far != far {
+            self.camera.set_near_far(near, auto_far);
+        }
+    }
+
+    pub fn set_sensitivity_x(&mut self, sensitivity: f32) {
+        self.sensitivity_x = sensitivity.max(0.);
+    }
+
+    pub fn get_sensitivity_x(&self) -> f32 {
+        self.sensitivity_x
+    }
+
+    pub fn set_sensitivity_y(&mut self, sensitivity: f32) {
+        self.sensitivity_y = sensitivity.max(0.);
+    }
+
+    pub fn get_sensitivity_y(&self) -> f32 {
+        self.sensitivity_y
+    }
+
+    // When inverted, moving the mouse up looks down
+    pub fn set_invert_y(&mut self, invert_y: bool) {
+        self.invert_y = invert_y;
+    }
+
+    pub fn get_invert_y(&self) -> bool {
+        self.invert_y
+    }
+
+    pub fn set_gamepad_deadzone(&mut self, deadzone: f32) {
+        self.gamepad_deadzone = deadzone.clamp(0., Self::MAX_GAMEPAD_DEADZONE);
+    }
+
+    pub fn get_gamepad_deadzone(&self) -> f32 {
+        self.gamepad_deadzone
+    }
+
+    pub fn set_gamepad_sensitivity(&mut self, sensitivity: f32) {
+        self.gamepad_sensitivity = sensitivity.max(0.);
+    }
+
+    pub fn get_gamepad_sensitivity(&self) -> f32 {
+        self.gamepad_sensitivity
+    }
+
+    pub fn set_rotation_mode(&mut self, rotation_mode: RotationMode) {
+        self.rotation_mode = rotation_mode;
+    }
+
+    pub fn get_rotation_mode(&self) -> RotationMode {
+        self.rotation_mode
+    }
+
     pub fn get_camera_matrix(&self) -> Matrix4<f32> {
         self.camera.get_camera_matrix()
     }
 
+    pub fn get_rotation_camera_matrix(&self) -> Matrix4<f32> {
+        self.camera.get_rotation_camera_matrix()
+    }
+
+    pub fn eye(&self) -> Point3<f32> {
+        self.camera.eye()
+    }
+
+    pub fn pose(&self) -> CameraPose {
+        self.camera.pose()
+    }
+
+    pub fn set_pose(&mut self, pose: CameraPose) {
+        self.camera.set_pose(pose);
+    }
+
+    // Deltas are in pixels, as for mouse motion, sensitivity and Y inversion are applied
+    pub fn rotate(&mut self, delta_x: f32, delta_y: f32) {
+        let delta_y = if self.invert_y { -delta_y } else { delta_y };
+        self.turn(
+            delta_x * self.rotation_speed * self.sensitivity_x,
+            delta_y * self.rotation_speed * self.sensitivity_y,
+        );
+    }
+
+    // Positive pan turns right, positive tilt looks down
+    fn turn(&mut self, pan: f32, tilt: f32) {
+        match self.rotation_mode {
+            RotationMode::Free => {
+                self.camera.pan(pan);
+                self.camera.tilt(tilt);
+            }
+            RotationMode::WorldUp => {
+                let forward = self.camera.forward();
+                let yaw = forward.x.atan2(forward.z) + pan;
+                let pitch = (forward.y.clamp(-1., 1.).asin() - tilt)
+                    .clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+                let direction = vec3(
+                    yaw.sin() * pitch.cos(),
+                    pitch.sin(),
+                    yaw.cos() * pitch.cos(),
+                );
+                let eye = self.camera.eye();
+                self.camera.look_at(eye, eye + direction, Vector3::unit_y());
+            }
+        }
+    }
+
+    // Positive values move the camera forward
+    pub fn zoom(&mut self, delta: f32) {
+        self.camera.move_z(delta * self.key_speed);
+        self.adjust_far_plane();
+    }
+
     pub fn mouse_event_listener(&mut self, event: &DeviceEvent) {
         match event {
             DeviceEvent::MouseMotion { delta } => {
-                self.camera.pan(delta.0 as f32 * self.rotation_speed);
-                self.camera.tilt(delta.1 as f32 * self.rotation_speed);
+                self.rotate(delta.0 as f32, delta.1 as f32);
             }
             DeviceEvent::MouseWheel {
                 delta: _scroll_delta,
@@ -219,7 +745,45 @@ impl WinitCameraAdapter {
         }
     }
 
+    // Left stick moves the camera, right stick rotates it
+    #[cfg(feature = "gamepad")]
+    pub fn gamepad_event_listener(&mut self, event: &gilrs::EventType) {
+        let gilrs::EventType::AxisChanged(axis, value, _) = *event else {
+            if *event == gilrs::EventType::Disconnected {
+                self.gamepad_sticks = GamepadSticks::default();
+            }
+            return;
+        };
+        let value = self.apply_deadzone(value);
+        match axis {
+            gilrs::Axis::LeftStickX => self.gamepad_sticks.move_x = value,
+            gilrs::Axis::LeftStickY => self.gamepad_sticks.move_z = value,
+            gilrs::Axis::RightStickX => self.gamepad_sticks.look_x = value,
+            gilrs::Axis::RightStickY => self.gamepad_sticks.look_y = value,
+            _ => {}
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn apply_deadzone(&self, value: f32) -> f32 {
+        if value.abs() < self.gamepad_deadzone {
+            return 0.;
+        }
+        // Rescale so the output still spans the full range outside of the deadzone
+        value.signum() * (value.abs() - self.gamepad_deadzone) / (1. - self.gamepad_deadzone)
+    }
+
     pub fn update(&mut self) {
+        if !self.gamepad_sticks.is_idle() {
+            let sticks = &self.gamepad_sticks;
+            let move_speed = self.key_speed * self.gamepad_sensitivity;
+            let look_speed = Self::GAMEPAD_LOOK_SPEED * self.gamepad_sensitivity;
+            self.camera.move_x(sticks.move_x * move_speed);
+            self.camera.move_z(sticks.move_z * move_speed);
+            let (pan, tilt) = (sticks.look_x * look_speed, -sticks.look_y * look_speed);
+            self.turn(pan, tilt);
+            self.adjust_far_plane();
+        }
         if self.enabled_keys.is_empty() {
             return;
         }
@@ -234,6 +798,7 @@ impl WinitCameraAdapter {
                 _ => {}
             };
         }
+        self.adjust_far_plane();
         debug!("{:?}", -self.as_ref().view);
     }
 }
@@ -243,3 +808,52 @@ impl AsRef<Camera> for WinitCameraAdapter {
         &self.camera
     }
 }
+
+impl AsMut<Camera> for WinitCameraAdapter {
+    fn as_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    #[test]
+    fn eye_is_recovered_from_look_at() {
+        let mut camera = Camera::from(PerspectiveConfig::default());
+        let eye = Point3::new(3., -2., 7.);
+        camera.look_at(eye, Point3::new(0., 1., 0.), Vector3::unit_y());
+        assert!((camera.eye() - eye).magnitude() < EPSILON);
+    }
+
+    #[test]
+    fn eye_is_recovered_from_perspective_config() {
+        let eye = Point3::new(-4., 6., 2.5);
+        let camera = Camera::from(PerspectiveConfig {
+            eye,
+            center: Point3::new(1., 0., -1.),
+            ..Default::default()
+        });
+        assert!((camera.eye() - eye).magnitude() < EPSILON);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pose_round_trips_through_json() {
+        let mut camera = Camera::from(OrthogonalConfig::default());
+        camera.look_at(
+            Point3::new(1., 2., -3.),
+            Point3::new(0., 0., 0.),
+            Vector3::unit_y(),
+        );
+        let json = serde_json::to_string(&camera.pose()).unwrap();
+        let pose: CameraPose = serde_json::from_str(&json).unwrap();
+        let mut restored = Camera::from(PerspectiveConfig::default());
+        restored.set_pose(pose);
+        assert_eq!(restored.view, camera.view);
+        assert_eq!(restored.projection(), camera.projection());
+    }
+}
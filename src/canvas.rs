@@ -0,0 +1,113 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::draw_context::{DrawContext, Drawable, DrawableOptions, Vertex};
+
+const CANVAS_PRELUDE_SHADER: &str = concat!(
+    include_str!("shaders/scene_globals.wgsl"),
+    include_str!("shaders/canvas_prelude.wgsl")
+);
+
+// Single triangle covering the whole clip space, in counter-clockwise order
+const FULLSCREEN_TRIANGLE: [Vertex; 3] = [
+    Vertex {
+        position: [-1.0, -1.0, 0.0],
+        color: [0.0, 0.0, 0.0],
+    },
+    Vertex {
+        position: [3.0, -1.0, 0.0],
+        color: [0.0, 0.0, 0.0],
+    },
+    Vertex {
+        position: [-1.0, 3.0, 0.0],
+        color: [0.0, 0.0, 0.0],
+    },
+];
+
+// Shadertoy conventions: time in seconds, resolution in pixels, mouse with xy the current
+// position and zw the position of the last click, in pixels from the bottom-left corner
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CanvasUniforms {
+    pub time: f32,
+    pub resolution: [f32; 2],
+    pub mouse: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct CanvasUniform {
+    time: f32,
+    _padding: f32,
+    resolution: [f32; 2],
+    mouse: [f32; 4],
+}
+
+impl From<&CanvasUniforms> for CanvasUniform {
+    fn from(uniforms: &CanvasUniforms) -> Self {
+        CanvasUniform {
+            time: uniforms.time,
+            _padding: 0.,
+            resolution: uniforms.resolution,
+            mouse: uniforms.mouse,
+        }
+    }
+}
+
+// Fullscreen drawable running fragment code written like on shadertoy, with a
+// `fn main_image(frag_coord: vec2<f32>) -> vec4<f32>` function and the `globals.time`,
+// `globals.resolution` and `globals.mouse` uniforms, updated by the app on each frame.
+// The drawable has no depth test, so the scenario must not use depth, see DynScenario::uses_depth
+pub fn create_canvas_shadertoy(
+    context: &DrawContext,
+    fragment_wgsl: &str,
+) -> anyhow::Result<Drawable> {
+    let shader_source = format!("{CANVAS_PRELUDE_SHADER}{fragment_wgsl}");
+    let shader_module = context.create_shader_module("Canvas Shader", &shader_source)?;
+    let vertex_state = wgpu::VertexState {
+        module: &shader_module,
+        entry_point: Some("vtx_main"),
+        buffers: std::slice::from_ref(&context.vertex_buffer_layout),
+        compilation_options: Default::default(),
+    };
+    let fragment_state = wgpu::FragmentState {
+        module: &shader_module,
+        entry_point: Some("frg_main"),
+        targets: &[Some(wgpu::ColorTargetState {
+            format: context.surface_format(),
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+        })],
+        compilation_options: Default::default(),
+    };
+    Ok(Drawable::init_direct_with_options(
+        context,
+        &FULLSCREEN_TRIANGLE,
+        vertex_state,
+        fragment_state,
+        DrawableOptions {
+            depth_test: false,
+            ..Default::default()
+        },
+    ))
+}
@@ -22,12 +22,24 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use std::sync::Arc;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::future::Future;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
 
+use crate::canvas::{CanvasUniform, CanvasUniforms};
 use crate::draw_context::Drawable::{Direct, Indexed};
-use crate::scenario::Scenario;
-use anyhow::anyhow;
-use log::debug;
+use crate::lights::{Light, LightUniform};
+use crate::post_process::{PostProcess, PostProcessSource};
+use crate::primitives::color::linear_to_srgb;
+use crate::scenario::{DynScenario, RenderStats, Scenario};
+use crate::textures::{Texture2D, Texture2DDescriptor};
+use anyhow::{anyhow, bail, Context};
+use log::{debug, error, info, warn};
+use web_time::Duration;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu::{
     BindGroupLayoutDescriptor, BindingType, BufferBindingType, ShaderStages, SurfaceConfiguration,
@@ -42,6 +54,32 @@ const M4X4_ID_UNIFORM: [[f32; 4]; 4] = [
     [0., 0., 0., 1.],
 ];
 
+// Transform matrix followed by the opacity, shaders only reading the matrix stay compatible
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct TransformUniform {
+    model: [[f32; 4]; 4],
+    opacity: f32,
+    _padding: [f32; 3],
+}
+
+const TRANSFORM_UNIFORM_ID: TransformUniform = TransformUniform {
+    model: M4X4_ID_UNIFORM,
+    opacity: 1.0,
+    _padding: [0.; 3],
+};
+pub(crate) const TRANSFORM_UNIFORM_SIZE: wgpu::BufferAddress =
+    std::mem::size_of::<TransformUniform>() as wgpu::BufferAddress;
+const TRANSFORM_OPACITY_OFFSET: wgpu::BufferAddress =
+    std::mem::offset_of!(TransformUniform, opacity) as wgpu::BufferAddress;
+
+pub const ALPHA_SHADER: &str = include_str!("shaders/alpha.wgsl");
+// Renders the normals as colors, for drawables with a NormalVertex layout
+pub const NORMALS_DEBUG_SHADER: &str = include_str!("shaders/normals_debug.wgsl");
+const FXAA_SHADER: &str = include_str!("shaders/fxaa.wgsl");
+const UPSCALE_SHADER: &str = include_str!("shaders/upscale.wgsl");
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Dimensions {
     pub width: u32,
     pub height: u32,
@@ -75,6 +113,101 @@ impl Vertex {
     }
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct NormalVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+impl NormalVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x3];
+
+    fn vertex_buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<NormalVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TexturedVertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl TexturedVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2];
+
+    fn vertex_buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TexturedVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// Layout of interleaved vertices of type V, described with (shader location, format, offset)
+// tuples, for vertex data not matching Vertex, NormalVertex or TexturedVertex, e.g. loaded meshes.
+// Drawables keep all the attributes in their single vertex buffer.
+#[derive(Clone, Debug)]
+pub struct InterleavedLayout {
+    array_stride: wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode,
+    attributes: Vec<wgpu::VertexAttribute>,
+}
+
+impl InterleavedLayout {
+    pub fn new<V: bytemuck::Pod>(
+        step_mode: wgpu::VertexStepMode,
+        layout: &[(u32, wgpu::VertexFormat, wgpu::BufferAddress)],
+    ) -> Self {
+        let array_stride = std::mem::size_of::<V>() as wgpu::BufferAddress;
+        let attributes = layout
+            .iter()
+            .map(|&(shader_location, format, offset)| {
+                assert!(
+                    offset + format.size() <= array_stride,
+                    "Attribute at location {shader_location} is out of the vertex"
+                );
+                wgpu::VertexAttribute {
+                    format,
+                    offset,
+                    shader_location,
+                }
+            })
+            .collect();
+        InterleavedLayout {
+            array_stride,
+            step_mode,
+            attributes,
+        }
+    }
+
+    fn from_vertex_buffer_layout(layout: &wgpu::VertexBufferLayout) -> Self {
+        InterleavedLayout {
+            array_stride: layout.array_stride,
+            step_mode: layout.step_mode,
+            attributes: layout.attributes.to_vec(),
+        }
+    }
+
+    pub fn vertex_buffer_layout(&self) -> wgpu::VertexBufferLayout<'_> {
+        wgpu::VertexBufferLayout {
+            array_stride: self.array_stride,
+            step_mode: self.step_mode,
+            attributes: &self.attributes,
+        }
+    }
+}
+
 impl Default for Vertex {
     fn default() -> Self {
         Vertex {
@@ -86,10 +219,85 @@ impl Default for Vertex {
 
 struct BaseDrawable {
     render_pipeline: wgpu::RenderPipeline,
+    // Only for opaque drawables created with DrawContext::set_depth_prepass enabled
+    depth_prepass_pipeline: Option<wgpu::RenderPipeline>,
+    // Pipeline inputs, kept to create the pipeline again with other shaders
+    vertex_layouts: Vec<InterleavedLayout>,
+    color_targets: Vec<Option<wgpu::ColorTargetState>>,
     vertex_buffer: wgpu::Buffer,
     transform_buffer: wgpu::Buffer,
     transform_bind_group: wgpu::BindGroup,
     blend_color_opacity: wgpu::Color,
+    instance_buffer: Option<wgpu::Buffer>,
+    instance_count: u32,
+    options: DrawableOptions,
+    texture_bind_group: Rc<wgpu::BindGroup>,
+    // None when a module was not created with DrawContext::create_shader_module, the pipelines
+    // then cannot be refreshed
+    shaders: Option<PipelineShaders>,
+    // DrawContext::pipeline_generation the pipelines were created with
+    pipeline_generation: u32,
+}
+
+// Shaders of the pipelines, kept to create them again when the depth or sample settings change
+#[derive(Clone)]
+struct PipelineShaders {
+    vertex: StageShader,
+    fragment: StageShader,
+}
+
+#[derive(Clone)]
+struct StageShader {
+    module: Rc<wgpu::ShaderModule>,
+    entry_point: Option<String>,
+    constants: HashMap<String, f64>,
+    zero_initialize_workgroup_memory: bool,
+}
+
+impl PipelineShaders {
+    fn new(
+        context: &DrawContext,
+        vertex_state: &wgpu::VertexState,
+        fragment_state: &wgpu::FragmentState,
+    ) -> Option<Self> {
+        Some(PipelineShaders {
+            vertex: StageShader::new(
+                context,
+                vertex_state.module,
+                vertex_state.entry_point,
+                &vertex_state.compilation_options,
+            )?,
+            fragment: StageShader::new(
+                context,
+                fragment_state.module,
+                fragment_state.entry_point,
+                &fragment_state.compilation_options,
+            )?,
+        })
+    }
+}
+
+impl StageShader {
+    fn new(
+        context: &DrawContext,
+        module: &wgpu::ShaderModule,
+        entry_point: Option<&str>,
+        compilation_options: &wgpu::PipelineCompilationOptions,
+    ) -> Option<Self> {
+        Some(StageShader {
+            module: context.shared_shader_module(module)?,
+            entry_point: entry_point.map(str::to_owned),
+            constants: compilation_options.constants.clone(),
+            zero_initialize_workgroup_memory: compilation_options.zero_initialize_workgroup_memory,
+        })
+    }
+
+    fn compilation_options(&self) -> wgpu::PipelineCompilationOptions<'_> {
+        wgpu::PipelineCompilationOptions {
+            constants: &self.constants,
+            zero_initialize_workgroup_memory: self.zero_initialize_workgroup_memory,
+        }
+    }
 }
 
 pub struct DirectRenderingDrawable {
@@ -100,7 +308,162 @@ pub struct DirectRenderingDrawable {
 pub struct IndexedRenderingDrawable {
     base: BaseDrawable,
     index_buffer: wgpu::Buffer,
+    index_format: wgpu::IndexFormat,
     index_count: u32,
+    // When empty, all the indices are drawn at once
+    submeshes: Vec<Submesh>,
+}
+
+// Range of indices drawn with its own draw call, e.g. a material group of a loaded model. Without
+// texture, the drawable texture is used.
+pub struct Submesh {
+    pub indices: std::ops::Range<u32>,
+    texture_bind_group: Option<Rc<wgpu::BindGroup>>,
+}
+
+impl Submesh {
+    pub fn new(start: u32, count: u32) -> Self {
+        Submesh {
+            indices: start..start + count,
+            texture_bind_group: None,
+        }
+    }
+
+    pub fn with_texture(mut self, texture: &Texture2D) -> Self {
+        self.texture_bind_group = Some(Rc::clone(texture.bind_group()));
+        self
+    }
+}
+
+pub enum IndexData {
+    U16(Vec<[u16; 3]>),
+    U32(Vec<[u32; 3]>),
+}
+
+impl IndexData {
+    // 16-bit indices are used when they can address all the vertices, 32-bit ones otherwise
+    pub fn from_triangles(vertex_count: usize, triangles: Vec<[u32; 3]>) -> Self {
+        debug_assert!(
+            triangles
+                .iter()
+                .flatten()
+                .all(|&i| (i as usize) < vertex_count),
+            "Triangle index out of the vertex range"
+        );
+        if vertex_count <= u16::MAX as usize + 1 {
+            IndexData::U16(
+                triangles
+                    .into_iter()
+                    .map(|triangle| triangle.map(|i| i as u16))
+                    .collect(),
+            )
+        } else {
+            IndexData::U32(triangles)
+        }
+    }
+
+    fn contents(&self) -> &[u8] {
+        match self {
+            IndexData::U16(triangles) => bytemuck::cast_slice(triangles),
+            IndexData::U32(triangles) => bytemuck::cast_slice(triangles),
+        }
+    }
+
+    fn format(&self) -> wgpu::IndexFormat {
+        match self {
+            IndexData::U16(_) => wgpu::IndexFormat::Uint16,
+            IndexData::U32(_) => wgpu::IndexFormat::Uint32,
+        }
+    }
+
+    fn index_count(&self) -> u32 {
+        let triangle_count = match self {
+            IndexData::U16(triangles) => triangles.len(),
+            IndexData::U32(triangles) => triangles.len(),
+        };
+        3 * triangle_count as u32
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    #[default]
+    Replace,
+    AlphaBlend,
+    Additive,
+    // Blends with the constant set by Drawable::set_blend_color_opacity
+    ConstantOpacity,
+}
+
+impl BlendMode {
+    pub fn blend_state(self) -> wgpu::BlendState {
+        match self {
+            BlendMode::Replace => wgpu::BlendState::REPLACE,
+            BlendMode::AlphaBlend => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+            BlendMode::ConstantOpacity => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Constant,
+                    dst_factor: wgpu::BlendFactor::OneMinusConstant,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: Default::default(),
+            },
+        }
+    }
+}
+
+// Coordinates are left-handed: X to the right, Y up and Z forward, away from the viewer. Front
+// faces are counter-clockwise as seen from the camera, which is what Camera::get_camera_matrix
+// preserves: its Z switch before the projection does not change the on-screen winding.
+// Geometry from right-handed sources, once its Z is mirrored, ends up clockwise, hence the
+// front_face option (see the glTF loader).
+#[derive(Clone, Debug)]
+pub struct DrawableOptions {
+    pub topology: wgpu::PrimitiveTopology,
+    pub front_face: wgpu::FrontFace,
+    // Without depth test, e.g. for fullscreen 2D drawables, the drawable can only be rendered in
+    // a scenario not using depth, see DynScenario::uses_depth
+    pub depth_test: bool,
+    // Drawn over what was rendered before it, whatever its depth, and without writing the depth,
+    // e.g. for gizmos. To render after the other drawables.
+    pub always_visible: bool,
+    // Back faces are not culled, e.g. for quads or open meshes. Lit shaders have to flip the
+    // normal of back faces, as done in the Lambert shader.
+    pub double_sided: bool,
+    // Added to the depth of the fragments, e.g. to draw a grid or decals over a coplanar surface
+    // without z-fighting. Negative values bring the drawable nearer, positive ones with
+    // reversed-Z. Only valid with triangle topologies.
+    pub depth_bias: wgpu::DepthBiasState,
+    // Only used when the draw context has a stencil buffer, see DrawContextConfig
+    pub stencil: wgpu::StencilState,
+    pub stencil_reference: u32,
+}
+
+impl Default for DrawableOptions {
+    fn default() -> Self {
+        DrawableOptions {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            front_face: wgpu::FrontFace::Ccw,
+            depth_test: true,
+            always_visible: false,
+            double_sided: false,
+            depth_bias: Default::default(),
+            stencil: Default::default(),
+            stencil_reference: 0,
+        }
+    }
 }
 
 pub enum Drawable {
@@ -109,90 +472,121 @@ pub enum Drawable {
 }
 
 impl Drawable {
-    pub fn init_direct(
+    pub fn init_direct<V: bytemuck::Pod>(
+        context: &DrawContext,
+        vertex_slice: &[V],
+        vertex_state: wgpu::VertexState,
+        fragment_state: wgpu::FragmentState,
+    ) -> Self {
+        Self::init_direct_with_options(
+            context,
+            vertex_slice,
+            vertex_state,
+            fragment_state,
+            DrawableOptions::default(),
+        )
+    }
+
+    pub fn init_direct_with_options<V: bytemuck::Pod>(
         context: &DrawContext,
-        vertex_slice: &[Vertex],
+        vertex_slice: &[V],
         vertex_state: wgpu::VertexState,
         fragment_state: wgpu::FragmentState,
+        options: DrawableOptions,
     ) -> Self {
         let vertex_count = vertex_slice.len() as u32;
-        let base = Self::init_base(context, vertex_slice, vertex_state, fragment_state);
+        let base = Self::init_base(context, vertex_slice, vertex_state, fragment_state, options);
         Direct(DirectRenderingDrawable { base, vertex_count })
     }
 
-    pub fn init_indexed(
+    pub fn init_indexed<V: bytemuck::Pod>(
         context: &DrawContext,
-        vertex_slice: &[Vertex],
+        vertex_slice: &[V],
         vertex_indices: &[[u16; 3]],
         vertex_state: wgpu::VertexState,
         fragment_state: wgpu::FragmentState,
     ) -> Self {
-        let base = Self::init_base(context, vertex_slice, vertex_state, fragment_state);
+        let options = DrawableOptions::default();
+        let base = Self::init_base(context, vertex_slice, vertex_state, fragment_state, options);
+        Self::init_indexed_base(
+            context,
+            base,
+            bytemuck::cast_slice(vertex_indices),
+            wgpu::IndexFormat::Uint16,
+            3 * vertex_indices.len() as u32,
+        )
+    }
+
+    pub fn init_indexed_with_options<V: bytemuck::Pod>(
+        context: &DrawContext,
+        vertex_slice: &[V],
+        index_data: &IndexData,
+        vertex_state: wgpu::VertexState,
+        fragment_state: wgpu::FragmentState,
+        options: DrawableOptions,
+    ) -> Self {
+        let base = Self::init_base(context, vertex_slice, vertex_state, fragment_state, options);
+        Self::init_indexed_base(
+            context,
+            base,
+            index_data.contents(),
+            index_data.format(),
+            index_data.index_count(),
+        )
+    }
+
+    fn init_indexed_base(
+        context: &DrawContext,
+        base: BaseDrawable,
+        index_contents: &[u8],
+        index_format: wgpu::IndexFormat,
+        index_count: u32,
+    ) -> Self {
         let index_buffer = context
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(vertex_indices),
+                contents: index_contents,
                 usage: wgpu::BufferUsages::INDEX,
             });
-        let index_count = 3 * vertex_indices.len() as u32;
         Indexed(IndexedRenderingDrawable {
             base,
             index_buffer,
+            index_format,
             index_count,
+            submeshes: Vec::new(),
         })
     }
 
-    fn init_base(
+    fn init_base<V: bytemuck::Pod>(
         context: &DrawContext,
-        vertex_slice: &[Vertex],
+        vertex_slice: &[V],
         vertex_state: wgpu::VertexState,
         fragment_state: wgpu::FragmentState,
+        options: DrawableOptions,
     ) -> BaseDrawable {
         let vertex_buffer = context
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Vertex Buffer"),
                 contents: bytemuck::cast_slice(vertex_slice),
-                usage: wgpu::BufferUsages::VERTEX,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             });
-        let render_pipeline =
-            context
-                .device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    cache: None,
-                    label: Some("Render Pipeline"),
-                    layout: Some(&context.pipeline_layout),
-                    vertex: vertex_state,
-                    fragment: Some(fragment_state),
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: Some(wgpu::Face::Back),
-                        unclipped_depth: false,
-                        polygon_mode: wgpu::PolygonMode::Fill, // wgpu::PolygonMode::Line
-                        conservative: false,
-                    },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: wgpu::TextureFormat::Depth32Float,
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::LessEqual,
-                        stencil: Default::default(),
-                        bias: Default::default(),
-                    }),
-                    multisample: wgpu::MultisampleState {
-                        count: context.multisample_config.get_multisample_count(),
-                        ..Default::default()
-                    },
-                    multiview: None,
-                });
+        let vertex_layouts = vertex_state
+            .buffers
+            .iter()
+            .map(InterleavedLayout::from_vertex_buffer_layout)
+            .collect();
+        let color_targets = fragment_state.targets.to_vec();
+        let shaders = PipelineShaders::new(context, &vertex_state, &fragment_state);
+        let (render_pipeline, depth_prepass_pipeline) =
+            Self::create_pipelines(context, vertex_state, fragment_state, &options);
         let transform_buffer =
             context
                 .device
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                     label: Some("Transform Buffer"),
-                    contents: bytemuck::cast_slice(&M4X4_ID_UNIFORM),
+                    contents: bytemuck::bytes_of(&TRANSFORM_UNIFORM_ID),
                     usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
                 });
         let transform_bind_group = context
@@ -208,11 +602,255 @@ impl Drawable {
         let blend_color_opacity = wgpu::Color::WHITE;
         BaseDrawable {
             render_pipeline,
+            depth_prepass_pipeline,
+            vertex_layouts,
+            color_targets,
             vertex_buffer,
             transform_buffer,
             transform_bind_group,
             blend_color_opacity,
+            instance_buffer: None,
+            instance_count: 1,
+            options,
+            texture_bind_group: Rc::clone(&context.default_texture_bind_group),
+            shaders,
+            pipeline_generation: context.pipeline_generation,
+        }
+    }
+
+    // Blending drawables are not in the depth prepass, as they must not hide what is behind them
+    fn create_pipelines(
+        context: &DrawContext,
+        vertex_state: wgpu::VertexState,
+        fragment_state: wgpu::FragmentState,
+        options: &DrawableOptions,
+    ) -> (wgpu::RenderPipeline, Option<wgpu::RenderPipeline>) {
+        let is_opaque = Self::is_opaque_targets(fragment_state.targets);
+        let depth_prepass =
+            context.depth_prepass && options.depth_test && !options.always_visible && is_opaque;
+        let depth_prepass_pipeline = depth_prepass
+            .then(|| Self::create_depth_prepass_pipeline(context, vertex_state.clone(), options));
+        let render_pipeline = Self::create_render_pipeline(
+            context,
+            vertex_state,
+            fragment_state,
+            options,
+            depth_prepass,
+        );
+        (render_pipeline, depth_prepass_pipeline)
+    }
+
+    fn is_opaque_targets(color_targets: &[Option<wgpu::ColorTargetState>]) -> bool {
+        color_targets
+            .iter()
+            .flatten()
+            .all(|target| matches!(target.blend, None | Some(wgpu::BlendState::REPLACE)))
+    }
+
+    // Drawables of the depth prepass are also accepted at equal depth in the main pass. Depth
+    // writes are kept, so a scenario not rendering the prepass still gets correct results.
+    fn create_render_pipeline(
+        context: &DrawContext,
+        vertex_state: wgpu::VertexState,
+        fragment_state: wgpu::FragmentState,
+        options: &DrawableOptions,
+        depth_prepass: bool,
+    ) -> wgpu::RenderPipeline {
+        context
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                cache: None,
+                label: Some("Render Pipeline"),
+                layout: Some(&context.pipeline_layout),
+                vertex: vertex_state,
+                fragment: Some(fragment_state),
+                primitive: wgpu::PrimitiveState {
+                    topology: options.topology,
+                    strip_index_format: None,
+                    front_face: options.front_face,
+                    cull_mode: match options.double_sided {
+                        true => None,
+                        false => Some(wgpu::Face::Back),
+                    },
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill, // wgpu::PolygonMode::Line
+                    conservative: false,
+                },
+                depth_stencil: options.depth_test.then(|| wgpu::DepthStencilState {
+                    format: context.depth_format,
+                    depth_write_enabled: !options.always_visible,
+                    depth_compare: match (options.always_visible, depth_prepass) {
+                        (true, _) => wgpu::CompareFunction::Always,
+                        (false, true) => context.depth_prepass_compare(),
+                        (false, false) => context.depth_compare(),
+                    },
+                    stencil: match context.has_stencil() {
+                        true => options.stencil.clone(),
+                        false => Default::default(),
+                    },
+                    bias: options.depth_bias,
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: context.multisample_config.get_multisample_count(),
+                    ..Default::default()
+                },
+                multiview: None,
+            })
+    }
+
+    fn create_depth_prepass_pipeline(
+        context: &DrawContext,
+        vertex_state: wgpu::VertexState,
+        options: &DrawableOptions,
+    ) -> wgpu::RenderPipeline {
+        context
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                cache: None,
+                label: Some("Depth Prepass Pipeline"),
+                layout: Some(&context.pipeline_layout),
+                vertex: vertex_state,
+                fragment: None,
+                primitive: wgpu::PrimitiveState {
+                    topology: options.topology,
+                    front_face: options.front_face,
+                    cull_mode: match options.double_sided {
+                        true => None,
+                        false => Some(wgpu::Face::Back),
+                    },
+                    ..Default::default()
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: context.depth_format,
+                    depth_write_enabled: true,
+                    depth_compare: context.depth_compare(),
+                    stencil: Default::default(),
+                    bias: options.depth_bias,
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: context.multisample_config.get_multisample_count(),
+                    ..Default::default()
+                },
+                multiview: None,
+            })
+    }
+
+    // The previous pipeline is kept if the new one is invalid, e.g. when reloading a shader
+    pub fn set_shader(
+        &mut self,
+        context: &DrawContext,
+        vertex_state: wgpu::VertexState,
+        fragment_state: wgpu::FragmentState,
+    ) -> anyhow::Result<()> {
+        let options = self.as_ref().options.clone();
+        let vertex_layouts = vertex_state
+            .buffers
+            .iter()
+            .map(InterleavedLayout::from_vertex_buffer_layout)
+            .collect();
+        let color_targets = fragment_state.targets.to_vec();
+        let shaders = PipelineShaders::new(context, &vertex_state, &fragment_state);
+        let (render_pipeline, depth_prepass_pipeline) = context
+            .with_validation_scope("Failed to create pipeline", || {
+                Self::create_pipelines(context, vertex_state, fragment_state, &options)
+            })?;
+        let base = self.as_mut();
+        base.render_pipeline = render_pipeline;
+        base.depth_prepass_pipeline = depth_prepass_pipeline;
+        base.vertex_layouts = vertex_layouts;
+        base.color_targets = color_targets;
+        base.shaders = shaders;
+        base.pipeline_generation = context.pipeline_generation;
+        Ok(())
+    }
+
+    // Creates the pipelines again when the settings baked in them changed since, see
+    // DrawContext::set_reverse_z and DrawContext::set_anti_aliasing. Done by Scene3D::update for
+    // its objects, other drawables have to be refreshed by the scenario, e.g. in its update. A
+    // failure is only reported once, the previous pipelines being kept.
+    pub fn refresh_pipelines(&mut self, context: &DrawContext) -> anyhow::Result<()> {
+        let base = self.as_mut();
+        if base.pipeline_generation == context.pipeline_generation {
+            return Ok(());
         }
+        base.pipeline_generation = context.pipeline_generation;
+        let Some(shaders) = base.shaders.clone() else {
+            bail!("Pipelines built from unshared shader modules must be rebuilt with set_shader");
+        };
+        let vertex_layouts = base.vertex_layouts.clone();
+        let color_targets = base.color_targets.clone();
+        let buffers: Vec<_> = vertex_layouts
+            .iter()
+            .map(InterleavedLayout::vertex_buffer_layout)
+            .collect();
+        let vertex_state = wgpu::VertexState {
+            module: &shaders.vertex.module,
+            entry_point: shaders.vertex.entry_point.as_deref(),
+            buffers: &buffers,
+            compilation_options: shaders.vertex.compilation_options(),
+        };
+        let fragment_state = wgpu::FragmentState {
+            module: &shaders.fragment.module,
+            entry_point: shaders.fragment.entry_point.as_deref(),
+            targets: &color_targets,
+            compilation_options: shaders.fragment.compilation_options(),
+        };
+        self.set_shader(context, vertex_state, fragment_state)
+    }
+
+    // Same as set_shader, reusing the vertex layouts, color targets and entry points of the current
+    // pipeline. The default entry points of the modules are used when the current shaders are not
+    // known, see refresh_pipelines.
+    pub fn replace_shaders(
+        &mut self,
+        context: &DrawContext,
+        vertex_module: &wgpu::ShaderModule,
+        fragment_module: &wgpu::ShaderModule,
+    ) -> anyhow::Result<()> {
+        let base = self.as_ref();
+        let vertex_layouts = base.vertex_layouts.clone();
+        let color_targets = base.color_targets.clone();
+        let buffers: Vec<_> = vertex_layouts
+            .iter()
+            .map(InterleavedLayout::vertex_buffer_layout)
+            .collect();
+        let (vertex_entry_point, fragment_entry_point) = match base.shaders {
+            Some(ref shaders) => (
+                shaders.vertex.entry_point.clone(),
+                shaders.fragment.entry_point.clone(),
+            ),
+            None => (None, None),
+        };
+        let vertex_state = wgpu::VertexState {
+            module: vertex_module,
+            entry_point: vertex_entry_point.as_deref(),
+            buffers: &buffers,
+            compilation_options: Default::default(),
+        };
+        let fragment_state = wgpu::FragmentState {
+            module: fragment_module,
+            entry_point: fragment_entry_point.as_deref(),
+            targets: &color_targets,
+            compilation_options: Default::default(),
+        };
+        self.set_shader(context, vertex_state, fragment_state)
+    }
+
+    // The stencil state is baked in the pipeline, so the shaders have to be given again
+    pub fn set_stencil_state(
+        &mut self,
+        context: &DrawContext,
+        vertex_state: wgpu::VertexState,
+        fragment_state: wgpu::FragmentState,
+        stencil: wgpu::StencilState,
+    ) -> anyhow::Result<()> {
+        let previous_stencil = std::mem::replace(&mut self.as_mut().options.stencil, stencil);
+        self.set_shader(context, vertex_state, fragment_state)
+            .inspect_err(|_| self.as_mut().options.stencil = previous_stencil)
+    }
+
+    pub fn set_stencil_reference(&mut self, reference: u32) {
+        self.as_mut().options.stencil_reference = reference;
     }
 
     pub fn set_transform(&mut self, context: &DrawContext, transform: impl AsRef<[[f32; 4]; 4]>) {
@@ -224,6 +862,85 @@ impl Drawable {
         );
     }
 
+    // For direct drawables the vertex count follows the new data, indexed drawables keep their indices
+    pub fn set_vertices<V: bytemuck::Pod>(&mut self, context: &DrawContext, vertices: &[V]) {
+        let contents: &[u8] = bytemuck::cast_slice(vertices);
+        let base = self.as_mut();
+        if base.vertex_buffer.size() >= contents.len() as wgpu::BufferAddress {
+            context.queue.write_buffer(&base.vertex_buffer, 0, contents);
+        } else {
+            base.vertex_buffer =
+                context
+                    .device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Vertex Buffer"),
+                        contents,
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    });
+        }
+        if let Direct(ref mut drawable) = self {
+            drawable.vertex_count = vertices.len() as u32;
+        }
+    }
+
+    pub fn set_texture(&mut self, texture: &Texture2D) {
+        self.as_mut().texture_bind_group = Rc::clone(texture.bind_group());
+    }
+
+    // Only for indexed drawables, an empty list draws all the indices again
+    pub fn set_submeshes(&mut self, submeshes: Vec<Submesh>) -> anyhow::Result<()> {
+        let Indexed(ref mut drawable) = self else {
+            bail!("Submeshes need an indexed drawable");
+        };
+        if let Some(submesh) = submeshes
+            .iter()
+            .find(|submesh| submesh.indices.end > drawable.index_count)
+        {
+            bail!(
+                "Submesh indices {:?} are out of the {} indices",
+                submesh.indices,
+                drawable.index_count
+            );
+        }
+        drawable.submeshes = submeshes;
+        Ok(())
+    }
+
+    pub fn set_instance_data<I: bytemuck::Pod>(&mut self, context: &DrawContext, instances: &[I]) {
+        let base = self.as_mut();
+        let contents: &[u8] = bytemuck::cast_slice(instances);
+        match base.instance_buffer {
+            Some(ref buffer) if buffer.size() == contents.len() as wgpu::BufferAddress => {
+                context.queue.write_buffer(buffer, 0, contents);
+            }
+            _ => {
+                base.instance_buffer = Some(context.device.create_buffer_init(
+                    &wgpu::util::BufferInitDescriptor {
+                        label: Some("Instance Buffer"),
+                        contents,
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    },
+                ));
+            }
+        }
+        base.instance_count = instances.len() as u32;
+    }
+
+    // Opacity read by shaders from the transform uniform, to be used with BlendMode::AlphaBlend
+    pub fn set_opacity(&mut self, context: &DrawContext, value: f32) {
+        context.queue.write_buffer(
+            &self.as_ref().transform_buffer,
+            TRANSFORM_OPACITY_OFFSET,
+            bytemuck::bytes_of(&value.clamp(0., 1.)),
+        );
+    }
+
+    // Without blending on its color targets, whatever its opacity, as for the depth prepass
+    pub fn is_opaque(&self) -> bool {
+        Self::is_opaque_targets(&self.as_ref().color_targets)
+    }
+
+    // Only has an effect on drawables using BlendMode::ConstantOpacity
     pub fn set_blend_color_opacity(&mut self, value: f64) {
         let value = value.clamp(0., 1.);
         self.as_mut().blend_color_opacity = wgpu::Color {
@@ -236,17 +953,85 @@ impl Drawable {
 
     pub fn render<'drawable>(&'drawable self, render_pass: &mut wgpu::RenderPass<'drawable>) {
         let base = self.as_ref();
-        render_pass.set_pipeline(&base.render_pipeline);
-        render_pass.set_bind_group(1, &base.transform_bind_group, &[]);
+        self.render_with_pipeline(
+            render_pass,
+            &base.render_pipeline,
+            &base.transform_bind_group,
+            0,
+        );
+    }
+
+    // Only writes the depth, does nothing if the drawable is not part of the depth prepass
+    pub fn render_depth_prepass<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+    ) {
+        let base = self.as_ref();
+        if let Some(ref pipeline) = base.depth_prepass_pipeline {
+            self.render_with_pipeline(render_pass, pipeline, &base.transform_bind_group, 0);
+        }
+    }
+
+    pub fn render_with_shared_transform<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        shared_transform: &'drawable SharedTransformBuffer,
+        index: u32,
+    ) {
+        self.render_with_pipeline(
+            render_pass,
+            &self.as_ref().render_pipeline,
+            &shared_transform.bind_group,
+            shared_transform.dynamic_offset(index),
+        );
+    }
+
+    fn render_with_pipeline<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        pipeline: &'drawable wgpu::RenderPipeline,
+        transform_bind_group: &'drawable wgpu::BindGroup,
+        transform_offset: wgpu::DynamicOffset,
+    ) {
+        let base = self.as_ref();
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(
+            DrawContext::BIND_GROUP_INDEX_TRANSFORM,
+            transform_bind_group,
+            &[transform_offset],
+        );
+        render_pass.set_bind_group(
+            DrawContext::BIND_GROUP_INDEX_TEXTURE,
+            &*base.texture_bind_group,
+            &[],
+        );
         render_pass.set_vertex_buffer(0, base.vertex_buffer.slice(..));
+        if let Some(ref instance_buffer) = base.instance_buffer {
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        }
         render_pass.set_blend_constant(base.blend_color_opacity);
+        render_pass.set_stencil_reference(base.options.stencil_reference);
         match self {
             Drawable::Direct(d) => {
-                render_pass.draw(0..d.vertex_count, 0..1);
+                render_pass.draw(0..d.vertex_count, 0..base.instance_count);
             }
             Drawable::Indexed(d) => {
-                render_pass.set_index_buffer(d.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                render_pass.draw_indexed(0..d.index_count, 0, 0..1);
+                render_pass.set_index_buffer(d.index_buffer.slice(..), d.index_format);
+                if d.submeshes.is_empty() {
+                    render_pass.draw_indexed(0..d.index_count, 0, 0..base.instance_count);
+                }
+                for submesh in &d.submeshes {
+                    let texture_bind_group = submesh
+                        .texture_bind_group
+                        .as_ref()
+                        .unwrap_or(&base.texture_bind_group);
+                    render_pass.set_bind_group(
+                        DrawContext::BIND_GROUP_INDEX_TEXTURE,
+                        &**texture_bind_group,
+                        &[],
+                    );
+                    render_pass.draw_indexed(submesh.indices.clone(), 0, 0..base.instance_count);
+                }
             }
         };
     }
@@ -270,34 +1055,553 @@ impl AsMut<BaseDrawable> for Drawable {
     }
 }
 
-pub struct MultiSampleConfig {
-    multisample_enabled: bool,
-    multisample_count: u32,
+#[derive(Default)]
+struct BindGroupLayoutCache {
+    layouts: RefCell<HashMap<Vec<wgpu::BindGroupLayoutEntry>, Rc<wgpu::BindGroupLayout>>>,
 }
 
-impl MultiSampleConfig {
-    pub fn get_multisample_count(&self) -> u32 {
-        match self.multisample_enabled {
-            true => self.multisample_count,
-            false => 1,
+impl BindGroupLayoutCache {
+    fn get_or_create(
+        &self,
+        device: &wgpu::Device,
+        entries: &[wgpu::BindGroupLayoutEntry],
+    ) -> Rc<wgpu::BindGroupLayout> {
+        let mut layouts = self.layouts.borrow_mut();
+        if let Some(layout) = layouts.get(entries) {
+            return Rc::clone(layout);
         }
-    }
-    pub fn is_multisample_enabled(&self) -> bool {
-        self.multisample_enabled
+        let layout = Rc::new(device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Cached bind group layout"),
+            entries,
+        }));
+        layouts.insert(entries.to_vec(), Rc::clone(&layout));
+        layout
     }
 }
 
-trait DeviceLocalExt {
-    fn create_depth_texture(
+// Many objects can share this single buffer, each one using its own slot selected
+// through a dynamic offset when rendering, instead of owning a buffer and a bind group.
+pub struct SharedTransformBuffer {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    stride: wgpu::BufferAddress,
+    capacity: u32,
+}
+
+impl SharedTransformBuffer {
+    pub fn new(context: &DrawContext, capacity: u32) -> Self {
+        let alignment = context.device.limits().min_uniform_buffer_offset_alignment;
+        let stride = TRANSFORM_UNIFORM_SIZE.next_multiple_of(alignment as wgpu::BufferAddress);
+        let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shared Transform Buffer"),
+            size: stride * capacity.max(1) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+        let bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Shared transform bind group"),
+                layout: &context.transform_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(TRANSFORM_UNIFORM_SIZE),
+                    }),
+                }],
+            });
+        for index in 0..capacity {
+            context.queue.write_buffer(
+                &buffer,
+                index as wgpu::BufferAddress * stride,
+                bytemuck::bytes_of(&TRANSFORM_UNIFORM_ID),
+            );
+        }
+        SharedTransformBuffer {
+            buffer,
+            bind_group,
+            stride,
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub fn set_transform(
+        &self,
+        context: &DrawContext,
+        index: u32,
+        transform: impl AsRef<[[f32; 4]; 4]>,
+    ) {
+        assert!(index < self.capacity, "Shared transform index out of range");
+        context.queue.write_buffer(
+            &self.buffer,
+            index as wgpu::BufferAddress * self.stride,
+            bytemuck::cast_slice(transform.as_ref()),
+        );
+    }
+
+    pub fn set_opacity(&self, context: &DrawContext, index: u32, value: f32) {
+        assert!(index < self.capacity, "Shared transform index out of range");
+        context.queue.write_buffer(
+            &self.buffer,
+            index as wgpu::BufferAddress * self.stride + TRANSFORM_OPACITY_OFFSET,
+            bytemuck::bytes_of(&value.clamp(0., 1.)),
+        );
+    }
+
+    pub fn dynamic_offset(&self, index: u32) -> wgpu::DynamicOffset {
+        assert!(index < self.capacity, "Shared transform index out of range");
+        (index as wgpu::BufferAddress * self.stride) as wgpu::DynamicOffset
+    }
+}
+
+pub trait RenderOverlay {
+    fn render_overlay(
+        &mut self,
+        context: &DrawContext,
+        encoder: &mut wgpu::CommandEncoder,
+        target_view: &wgpu::TextureView,
+    );
+}
+
+// Area of the render target, in fractions of its size so that it follows the resizes and the
+// render scale. The origin is the top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewportRect {
+    pub const FULL: ViewportRect = ViewportRect {
+        x: 0.,
+        y: 0.,
+        width: 1.,
+        height: 1.,
+    };
+
+    // Clamped in the target, and at least one pixel wide and high unless the target is empty
+    fn to_pixels(self, size: Dimensions) -> [u32; 4] {
+        let to_pixels = |start: f32, length: f32, size: u32| {
+            let to_pixel = |value: f32| (value.clamp(0., 1.) * size as f32).round() as u32;
+            let start_pixel = to_pixel(start).min(size.saturating_sub(1));
+            let end_pixel = to_pixel(start + length).max(start_pixel + 1).min(size);
+            (start_pixel, end_pixel - start_pixel)
+        };
+        let (x, width) = to_pixels(self.x, self.width, size.width);
+        let (y, height) = to_pixels(self.y, self.height, size.height);
+        [x, y, width, height]
+    }
+}
+
+// The scene is rendered once per viewport, with the camera matrix of the viewport, see
+// DrawContext::render_scene_with_viewports
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub camera_matrix: [[f32; 4]; 4],
+    pub rect: ViewportRect,
+}
+
+impl Viewport {
+    pub fn new(camera_matrix: impl Into<[[f32; 4]; 4]>, rect: ViewportRect) -> Self {
+        Viewport {
+            camera_matrix: camera_matrix.into(),
+            rect,
+        }
+    }
+}
+
+// Camera buffer of a viewport, kept between frames
+struct ViewportCamera {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+#[derive(Default)]
+struct RenderParams<'a> {
+    extra_targets: &'a [&'a ColorTarget],
+    // None clears with the scenario background, or the context clear color
+    color_load_op: Option<wgpu::LoadOp<wgpu::Color>>,
+    overlay: Option<&'a mut dyn RenderOverlay>,
+    post_process: Option<&'a PostProcess>,
+    viewports: Option<&'a [Viewport]>,
+}
+
+pub struct ColorTarget {
+    pub texture: wgpu::Texture,
+    multisample_texture: Option<wgpu::Texture>,
+}
+
+impl ColorTarget {
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+
+    fn create_views(&self) -> (wgpu::TextureView, Option<wgpu::TextureView>) {
+        let view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        match self.multisample_texture {
+            Some(ref multisample_texture) => (
+                multisample_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                Some(view),
+            ),
+            None => (view, None),
+        }
+    }
+}
+
+// MSAA multiplies the memory of the color and depth targets, FXAA is a post-process pass working
+// everywhere, WebGL included, but blurring a little the textures
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AntiAliasing {
+    None,
+    Msaa2,
+    #[default]
+    Msaa4,
+    Msaa8,
+    Fxaa,
+}
+
+impl AntiAliasing {
+    pub fn sample_count(self) -> u32 {
+        match self {
+            AntiAliasing::None | AntiAliasing::Fxaa => 1,
+            AntiAliasing::Msaa2 => 2,
+            AntiAliasing::Msaa4 => 4,
+            AntiAliasing::Msaa8 => 8,
+        }
+    }
+
+    fn multisample_config(self) -> MultiSampleConfig {
+        MultiSampleConfig {
+            multisample_enabled: self.sample_count() > 1,
+            multisample_count: self.sample_count(),
+        }
+    }
+}
+
+pub struct MultiSampleConfig {
+    multisample_enabled: bool,
+    multisample_count: u32,
+}
+
+impl MultiSampleConfig {
+    pub fn get_multisample_count(&self) -> u32 {
+        match self.multisample_enabled {
+            true => self.multisample_count,
+            false => 1,
+        }
+    }
+    pub fn is_multisample_enabled(&self) -> bool {
+        self.multisample_enabled
+    }
+}
+
+struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    readback_state: Arc<AtomicU8>,
+    timestamp_period: f32,
+    last_duration: Cell<Option<Duration>>,
+}
+
+impl GpuTimer {
+    const QUERY_COUNT: u32 = 2;
+    const BUFFER_SIZE: wgpu::BufferAddress = Self::QUERY_COUNT as wgpu::BufferAddress
+        * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+    const READBACK_IDLE: u8 = 0;
+    const READBACK_MAPPING: u8 = 1;
+    const READBACK_READY: u8 = 2;
+
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Timestamp Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: Self::QUERY_COUNT,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size: Self::BUFFER_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback Buffer"),
+            size: Self::BUFFER_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let timestamp_period = queue.get_timestamp_period();
+        debug!("Timestamp period: {timestamp_period} ns");
+        GpuTimer {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            readback_state: Arc::new(AtomicU8::new(Self::READBACK_IDLE)),
+            timestamp_period,
+            last_duration: Cell::new(None),
+        }
+    }
+
+    fn timestamp_writes(&self) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        }
+    }
+
+    fn collect(&self, device: &wgpu::Device) {
+        device.poll(wgpu::Maintain::Poll);
+        if self.readback_state.load(Ordering::Acquire) != Self::READBACK_READY {
+            return;
+        }
+        {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            let timestamps: &[u64] = bytemuck::cast_slice(&data);
+            let elapsed_ticks = timestamps[1].saturating_sub(timestamps[0]);
+            let elapsed_ns = elapsed_ticks as f64 * self.timestamp_period as f64;
+            self.last_duration
+                .set(Some(Duration::from_nanos(elapsed_ns as u64)));
+        }
+        self.readback_buffer.unmap();
+        self.readback_state
+            .store(Self::READBACK_IDLE, Ordering::Release);
+    }
+
+    // Returns true if a copy to the readback buffer was recorded, and so must be mapped after submit
+    fn resolve(&self, encoder: &mut wgpu::CommandEncoder) -> bool {
+        encoder.resolve_query_set(
+            &self.query_set,
+            0..Self::QUERY_COUNT,
+            &self.resolve_buffer,
+            0,
+        );
+        if self.readback_state.load(Ordering::Acquire) != Self::READBACK_IDLE {
+            return false;
+        }
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            Self::BUFFER_SIZE,
+        );
+        true
+    }
+
+    fn map_readback(&self) {
+        self.readback_state
+            .store(Self::READBACK_MAPPING, Ordering::Release);
+        let readback_state = Arc::clone(&self.readback_state);
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let state = match result {
+                    Ok(()) => Self::READBACK_READY,
+                    Err(_) => Self::READBACK_IDLE,
+                };
+                readback_state.store(state, Ordering::Release);
+            });
+    }
+}
+
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    // Tightly packed RGBA8 rows, sRGB encoded whatever the surface format, so ready to be saved
+    pub rgba: Vec<u8>,
+}
+
+impl CapturedFrame {
+    pub fn into_image(self) -> image::RgbaImage {
+        image::RgbaImage::from_raw(self.width, self.height, self.rgba)
+            .expect("Captured frame should match its dimensions")
+    }
+}
+
+// No f16 type in std, subnormals included
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 == 0 { 1. } else { -1. };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0. => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1. + mantissa / 1024.) * 2f32.powi(exponent - 15),
+    }
+}
+
+struct FrameCapture {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: wgpu::TextureFormat,
+    ready: Arc<AtomicBool>,
+}
+
+impl FrameCapture {
+    const BYTES_PER_PIXEL: u32 = 4;
+
+    fn record(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Self {
+        let (width, height) = (texture.width(), texture.height());
+        let bytes_per_pixel = Self::bytes_per_pixel(texture.format());
+        let padded_bytes_per_row =
+            (width * bytes_per_pixel).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Capture Buffer"),
+            size: padded_bytes_per_row as wgpu::BufferAddress * height as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        FrameCapture {
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            format: texture.format(),
+            ready: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn map_readback(&self) {
+        let ready = Arc::clone(&self.ready);
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| match result {
+                Ok(()) => ready.store(true, Ordering::Release),
+                Err(err) => warn!("Could not map frame capture buffer: {err}"),
+            });
+    }
+
+    fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    fn bytes_per_pixel(format: wgpu::TextureFormat) -> u32 {
+        format
+            .block_copy_size(None)
+            .unwrap_or(Self::BYTES_PER_PIXEL)
+    }
+
+    // Formats decoded by read, the usual surface formats
+    fn is_format_supported(format: wgpu::TextureFormat) -> bool {
+        matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm
+                | wgpu::TextureFormat::Rgba8UnormSrgb
+                | wgpu::TextureFormat::Bgra8Unorm
+                | wgpu::TextureFormat::Bgra8UnormSrgb
+                | wgpu::TextureFormat::Rgb10a2Unorm
+                | wgpu::TextureFormat::Rgba16Float
+        )
+    }
+
+    fn read(self) -> anyhow::Result<CapturedFrame> {
+        let row_size = (self.width * Self::bytes_per_pixel(self.format)) as usize;
+        let mut pixels = Vec::with_capacity(row_size * self.height as usize);
+        {
+            let data = self.buffer.slice(..).get_mapped_range();
+            for row in data.chunks(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..row_size]);
+            }
+        }
+        self.buffer.unmap();
+        Ok(CapturedFrame {
+            width: self.width,
+            height: self.height,
+            rgba: Self::decode(self.format, pixels)?,
+        })
+    }
+
+    // Tightly packed pixels of the format to sRGB encoded RGBA8
+    fn decode(format: wgpu::TextureFormat, mut pixels: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        if !Self::is_format_supported(format) {
+            bail!("Cannot decode captured frames of format {format:?}");
+        }
+        let rgba = match format {
+            // Linear values, encoded to sRGB as they are displayed
+            wgpu::TextureFormat::Rgba16Float => pixels
+                .chunks_exact(2)
+                .enumerate()
+                .map(|(index, half)| {
+                    let value = f16_to_f32(u16::from_le_bytes([half[0], half[1]]));
+                    let value = match index % 4 {
+                        3 => value,
+                        _ => linear_to_srgb(value),
+                    };
+                    (value.clamp(0., 1.) * 255.).round() as u8
+                })
+                .collect(),
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+                pixels
+                    .chunks_exact_mut(Self::BYTES_PER_PIXEL as usize)
+                    .for_each(|pixel| pixel.swap(0, 2));
+                pixels
+            }
+            // Display values like Rgba8Unorm, with 10 bit color channels and a 2 bit alpha
+            wgpu::TextureFormat::Rgb10a2Unorm => pixels
+                .chunks_exact(Self::BYTES_PER_PIXEL as usize)
+                .flat_map(|pixel| {
+                    let packed = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                    let channel = |shift: u32| (((packed >> shift) & 0x3ff) >> 2) as u8;
+                    [
+                        channel(0),
+                        channel(10),
+                        channel(20),
+                        ((packed >> 30) * 255 / 3) as u8,
+                    ]
+                })
+                .collect(),
+            _ => pixels,
+        };
+        Ok(rgba)
+    }
+}
+
+trait DeviceLocalExt {
+    fn create_depth_texture(
         &self,
         surface_config: &wgpu::SurfaceConfiguration,
         multisample_config: &MultiSampleConfig,
+        format: wgpu::TextureFormat,
     ) -> wgpu::Texture;
     fn create_multisample_texture(
         &self,
         surface_config: &wgpu::SurfaceConfiguration,
         multisample_config: &MultiSampleConfig,
     ) -> Option<wgpu::Texture>;
+    fn create_offscreen_texture(
+        &self,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::Texture;
 }
 
 impl DeviceLocalExt for wgpu::Device {
@@ -305,6 +1609,7 @@ impl DeviceLocalExt for wgpu::Device {
         &self,
         surface_config: &SurfaceConfiguration,
         multisample_config: &MultiSampleConfig,
+        format: wgpu::TextureFormat,
     ) -> Texture {
         self.create_texture(&wgpu::TextureDescriptor {
             label: Some("Depth Texture"),
@@ -317,7 +1622,7 @@ impl DeviceLocalExt for wgpu::Device {
             sample_count: multisample_config.get_multisample_count(),
             dimension: wgpu::TextureDimension::D2,
             view_formats: &[],
-            format: wgpu::TextureFormat::Depth32Float,
+            format,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
         })
     }
@@ -345,103 +1650,403 @@ impl DeviceLocalExt for wgpu::Device {
             false => None,
         }
     }
+
+    fn create_offscreen_texture(&self, surface_config: &SurfaceConfiguration) -> Texture {
+        self.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Texture"),
+            size: wgpu::Extent3d {
+                width: surface_config.width,
+                height: surface_config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: surface_config.format,
+            view_formats: &[],
+            usage: surface_config.usage,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AdapterConfig {
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    pub force_fallback_adapter: bool,
+}
+
+impl Default for AdapterConfig {
+    fn default() -> Self {
+        AdapterConfig {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+        }
+    }
+}
+
+impl AdapterConfig {
+    // Defaults overridden by WGPU_BACKEND (e.g. "vulkan,gl"), WGPU_POWER_PREF ("low" or "high")
+    // and WGPU_FORCE_FALLBACK_ADAPTER ("1" or "true") when they are set
+    pub fn from_env() -> Self {
+        let default = AdapterConfig::default();
+        AdapterConfig {
+            backends: wgpu::util::backend_bits_from_env().unwrap_or(default.backends),
+            power_preference: wgpu::util::power_preference_from_env()
+                .unwrap_or(default.power_preference),
+            force_fallback_adapter: std::env::var("WGPU_FORCE_FALLBACK_ADAPTER")
+                .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+                .unwrap_or(default.force_fallback_adapter),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DrawContextConfig {
+    pub adapter: AdapterConfig,
+    // Depth24PlusStencil8 instead of Depth32Float when enabled. Both formats are required by
+    // WebGPU on every backend, so there is no fallback to handle
+    pub stencil_enabled: bool,
+    // Reversed-Z maps the far plane to 0 and the near plane to 1, which spreads the float
+    // precision of Depth32Float evenly across the depth range. The app camera follows
+    // DrawContext::is_reverse_z, other cameras must be switched with Camera::set_reverse_z.
+    // Geometry beyond the far plane is still clipped, keeping it would need unclipped_depth and
+    // Features::DEPTH_CLIP_CONTROL. Can be changed afterwards with DrawContext::set_reverse_z.
+    pub reverse_z: bool,
+    // E.g. Rgba16Float for HDR output, the first sRGB format is used if unsupported by the
+    // surface. Pipelines bake the color target format, so drawables must be built afterwards
+    pub preferred_surface_format: Option<wgpu::TextureFormat>,
+    // Fifo when None or unsupported by the surface. Immediate or Mailbox disable vsync, the
+    // window then redraws as fast as possible, see AppConfig::target_fps
+    pub present_mode: Option<wgpu::PresentMode>,
+    // Initial anti-aliasing, disabled if unsupported by the formats, see
+    // DrawContext::set_anti_aliasing
+    pub anti_aliasing: AntiAliasing,
+}
+
+impl DrawContextConfig {
+    pub fn from_env() -> Self {
+        DrawContextConfig {
+            adapter: AdapterConfig::from_env(),
+            ..Default::default()
+        }
+    }
 }
 
 pub struct DrawContext {
-    _adapter: wgpu::Adapter,
+    adapter: wgpu::Adapter,
+    depth_format: wgpu::TextureFormat,
+    reverse_z: bool,
+    depth_enabled: bool,
+    depth_clear_value: Option<f32>,
+    depth_prepass: bool,
+    clear_color: wgpu::Color,
+    gpu_timer: Option<GpuTimer>,
+    gpu_timing_enabled: bool,
+    anti_aliasing: AntiAliasing,
+    // Only created with AntiAliasing::Fxaa
+    fxaa: Option<PostProcess>,
+    render_scale: f32,
+    // Only created with a render scale other than 1
+    upscale: Option<PostProcess>,
     multisample_texture: Option<wgpu::Texture>,
-    surface: wgpu::Surface<'static>,
+    // Views of the depth and multisample textures, only recreated on resize
+    depth_texture_view: wgpu::TextureView,
+    multisample_view: Option<wgpu::TextureView>,
+    surface: Option<wgpu::Surface<'static>>,
+    offscreen_texture: Option<wgpu::Texture>,
+    capture_requested: Cell<bool>,
+    frame_capture: RefCell<Option<FrameCapture>>,
+    // Set by the device lost callback, with the reason given by wgpu
+    device_lost: Arc<Mutex<Option<String>>>,
+    // Created on the first post-processed frame, and dropped on resize
+    post_process_source: RefCell<Option<Rc<PostProcessSource>>>,
+    // Grown to the largest number of viewports rendered at once
+    viewport_cameras: RefCell<Vec<ViewportCamera>>,
+    bind_group_layout_cache: BindGroupLayoutCache,
+    // Kept for pipelines with their own layout, see create_pipeline_layout
+    camera_bind_group_layout: Rc<wgpu::BindGroupLayout>,
+    scene_bind_group_layout: Rc<wgpu::BindGroupLayout>,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    scene_buffer: wgpu::Buffer,
+    globals_buffer: wgpu::Buffer,
+    scene_bind_group: wgpu::BindGroup,
+    // White 1x1 texture, for drawables without texture
+    default_texture_bind_group: Rc<wgpu::BindGroup>,
     pub multisample_config: MultiSampleConfig,
+    // Increased when a setting baked in the pipelines changes, see Drawable::refresh_pipelines
+    pipeline_generation: u32,
+    // Modules created with create_shader_module, so drawables can keep them to refresh their
+    // pipelines
+    shader_modules: RefCell<Vec<Weak<wgpu::ShaderModule>>>,
     pub depth_texture: wgpu::Texture,
     pub queue: wgpu::Queue,
     pub transform_bind_group_layout: wgpu::BindGroupLayout,
+    pub texture_bind_group_layout: Rc<wgpu::BindGroupLayout>,
     pub device: wgpu::Device,
     pub vertex_buffer_layout: wgpu::VertexBufferLayout<'static>,
+    pub normal_vertex_buffer_layout: wgpu::VertexBufferLayout<'static>,
+    pub textured_vertex_buffer_layout: wgpu::VertexBufferLayout<'static>,
+    // Prefer surface_format, size and aspect_ratio
     pub surface_config: wgpu::SurfaceConfiguration,
     pub pipeline_layout: wgpu::PipelineLayout,
 }
 
 impl DrawContext {
-    const DEFAULT_MULTISAMPLE_ENABLED: bool = true;
-    const DEFAULT_MULTISAMPLE_COUNT: u32 = 4;
+    const HEADLESS_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+    const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    const DEPTH_STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+    const DEFAULT_CLEAR_COLOR: wgpu::Color = wgpu::Color {
+        r: 0.0,
+        g: 0.5,
+        b: 0.5,
+        a: 1.0,
+    };
+    const MAX_ANISOTROPY_CLAMP: u16 = 16;
+    const MIN_RENDER_SCALE: f32 = 0.25;
+    const MAX_RENDER_SCALE: f32 = 2.;
     pub const BIND_GROUP_INDEX_CAMERA: u32 = 0;
+    pub const BIND_GROUP_INDEX_TRANSFORM: u32 = 1;
+    pub const BIND_GROUP_INDEX_SCENE: u32 = 2;
+    pub const BIND_GROUP_INDEX_TEXTURE: u32 = 3;
+    // Bindings of the scene group, declared in shaders/scene_globals.wgsl
+    pub const SCENE_BINDING_LIGHT: u32 = 0;
+    pub const SCENE_BINDING_GLOBALS: u32 = 1;
+    // Number of bind groups in the pipeline layout shared by the drawables
+    pub const BIND_GROUP_COUNT: u32 = 4;
 
     // FIXME winit window has size of 0 at startup for web browser, so also passing dimensions to draw context
     pub async fn new(
         window: Arc<Window>,
         dimensions: Option<Dimensions>,
+    ) -> anyhow::Result<DrawContext> {
+        Self::new_with_config(window, dimensions, &DrawContextConfig::from_env()).await
+    }
+
+    pub async fn new_with_config(
+        window: Arc<Window>,
+        dimensions: Option<Dimensions>,
+        config: &DrawContextConfig,
     ) -> anyhow::Result<DrawContext> {
         let (width, height) = match dimensions {
             Some(d) => (d.width, d.height),
             None => (window.inner_size().width, window.inner_size().height),
         };
-        let multisample_config = MultiSampleConfig {
-            multisample_enabled: Self::DEFAULT_MULTISAMPLE_ENABLED,
-            multisample_count: Self::DEFAULT_MULTISAMPLE_COUNT,
+        let instance = Self::create_instance(&config.adapter);
+        let surface = instance
+            .create_surface(Arc::clone(&window))
+            .context("Failed to create surface")?;
+        let adapter = Self::request_adapter(&instance, &config.adapter, Some(&surface)).await?;
+        let (device, queue) = Self::request_device(&adapter).await?;
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = Self::select_surface_format(&surface_caps, config);
+        let present_mode = Self::select_present_mode(&surface_caps, config);
+        let surface_config = wgpu::SurfaceConfiguration {
+            desired_maximum_frame_latency: 2,
+            // COPY_SRC is needed for frame captures, but is not supported by all surfaces
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC),
+            format: surface_format,
+            width,
+            height,
+            view_formats: vec![],
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            present_mode,
         };
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
+        surface.configure(&device, &surface_config);
+        Ok(Self::init(
+            adapter,
+            device,
+            queue,
+            Some(surface),
+            surface_config,
+            config,
+        ))
+    }
+
+    pub async fn new_headless(width: u32, height: u32) -> anyhow::Result<DrawContext> {
+        Self::new_headless_with_config(width, height, &DrawContextConfig::from_env()).await
+    }
+
+    pub async fn new_headless_with_config(
+        width: u32,
+        height: u32,
+        config: &DrawContextConfig,
+    ) -> anyhow::Result<DrawContext> {
+        if width == 0 || height == 0 {
+            bail!("Headless context dimensions must not be zero");
+        }
+        let instance = Self::create_instance(&config.adapter);
+        let adapter = Self::request_adapter(&instance, &config.adapter, None).await?;
+        let (device, queue) = Self::request_device(&adapter).await?;
+        let surface_config = wgpu::SurfaceConfiguration {
+            desired_maximum_frame_latency: 2,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: Self::HEADLESS_TEXTURE_FORMAT,
+            width,
+            height,
+            view_formats: vec![],
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        Ok(Self::init(
+            adapter,
+            device,
+            queue,
+            None,
+            surface_config,
+            config,
+        ))
+    }
+
+    fn select_surface_format(
+        surface_caps: &wgpu::SurfaceCapabilities,
+        config: &DrawContextConfig,
+    ) -> wgpu::TextureFormat {
+        let preferred_format = config.preferred_surface_format.filter(|format| {
+            let supported = surface_caps.formats.contains(format);
+            if !supported {
+                warn!(
+                    "Surface format {format:?} not supported, available formats: {:?}",
+                    surface_caps.formats
+                );
+            }
+            supported
         });
-        let surface = instance.create_surface(Arc::clone(&window)).unwrap();
+        let surface_format = preferred_format.unwrap_or_else(|| {
+            surface_caps
+                .formats
+                .iter()
+                .find(|f| f.is_srgb())
+                .copied()
+                .unwrap_or(surface_caps.formats[0])
+        });
+        info!("Using surface format {surface_format:?}");
+        surface_format
+    }
+
+    fn select_present_mode(
+        surface_caps: &wgpu::SurfaceCapabilities,
+        config: &DrawContextConfig,
+    ) -> wgpu::PresentMode {
+        let Some(present_mode) = config.present_mode else {
+            return wgpu::PresentMode::Fifo;
+        };
+        // The Auto modes always resolve to a supported mode
+        let auto_mode = matches!(
+            present_mode,
+            wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+        );
+        if !auto_mode && !surface_caps.present_modes.contains(&present_mode) {
+            warn!(
+                "Present mode {present_mode:?} not supported, available modes: {:?}",
+                surface_caps.present_modes
+            );
+            return wgpu::PresentMode::Fifo;
+        }
+        info!("Using present mode {present_mode:?}");
+        present_mode
+    }
+
+    fn create_instance(adapter_config: &AdapterConfig) -> wgpu::Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: adapter_config.backends,
+            ..Default::default()
+        })
+    }
+
+    async fn request_adapter(
+        instance: &wgpu::Instance,
+        adapter_config: &AdapterConfig,
+        compatible_surface: Option<&wgpu::Surface<'static>>,
+    ) -> anyhow::Result<wgpu::Adapter> {
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: Default::default(),
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
+                power_preference: adapter_config.power_preference,
+                force_fallback_adapter: adapter_config.force_fallback_adapter,
+                compatible_surface,
             })
             .await
-            .ok_or_else(|| anyhow!("Could not create WebGPU adapter"))?;
-        debug!("{:?}", adapter);
+            .ok_or_else(|| {
+                anyhow!("Could not find a WebGPU adapter matching {adapter_config:?}")
+            })?;
+        info!("Using adapter {:?}", adapter.get_info());
         debug!("{:?}", adapter.features());
+        Ok(adapter)
+    }
+
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+    ) -> anyhow::Result<(wgpu::Device, wgpu::Queue)> {
         let required_limits = if cfg!(target_arch = "wasm32") {
             wgpu::Limits::downlevel_webgl2_defaults()
         } else {
             wgpu::Limits::default()
         };
+        let max_bind_groups = adapter.limits().max_bind_groups;
+        if max_bind_groups < Self::BIND_GROUP_COUNT {
+            bail!(
+                "The adapter supports {max_bind_groups} bind groups, {} are needed",
+                Self::BIND_GROUP_COUNT
+            );
+        }
+        // Timestamp queries are only requested when available so other machines still work
+        let required_features = adapter.features() & wgpu::Features::TIMESTAMP_QUERY;
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Device Descriptor"),
-                    required_features: wgpu::Features::empty(),
+                    required_features,
                     required_limits,
                     memory_hints: wgpu::MemoryHints::Performance,
                 },
                 None,
             )
             .await
-            .unwrap();
-        let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
-        let surface_config = wgpu::SurfaceConfiguration {
-            desired_maximum_frame_latency: 2,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width,
-            height,
-            view_formats: vec![],
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
-            present_mode: wgpu::PresentMode::Fifo,
+            .context("Failed to request device")?;
+        Ok((device, queue))
+    }
+
+    fn init(
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        surface: Option<wgpu::Surface<'static>>,
+        surface_config: wgpu::SurfaceConfiguration,
+        config: &DrawContextConfig,
+    ) -> DrawContext {
+        let depth_format = match config.stencil_enabled {
+            true => Self::DEPTH_STENCIL_FORMAT,
+            false => Self::DEPTH_FORMAT,
         };
-        surface.configure(&device, &surface_config);
+        let formats = [surface_config.format, depth_format];
+        let anti_aliasing = match Self::check_sample_count(&adapter, formats, config.anti_aliasing)
+        {
+            Ok(()) => config.anti_aliasing,
+            Err(err) => {
+                warn!("{err:#}, anti-aliasing is disabled");
+                AntiAliasing::None
+            }
+        };
+        let multisample_config = anti_aliasing.multisample_config();
         let vertex_buffer_layout = Vertex::vertex_buffer_layout();
+        let normal_vertex_buffer_layout = NormalVertex::vertex_buffer_layout();
+        let textured_vertex_buffer_layout = TexturedVertex::vertex_buffer_layout();
+        let bind_group_layout_cache = BindGroupLayoutCache::default();
+        // Dynamic offset so that a SharedTransformBuffer can also be bound with this layout. Not
+        // cached, as the public field owns it.
         let transform_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Transform bind group"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(TRANSFORM_UNIFORM_SIZE),
                     },
                     count: None,
                 }],
@@ -451,20 +2056,19 @@ impl DrawContext {
             contents: bytemuck::cast_slice(&M4X4_ID_UNIFORM),
             usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
         });
-        let camera_bind_group_layout =
-            device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-                label: None,
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: ShaderStages::VERTEX,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-            });
+        let camera_bind_group_layout = bind_group_layout_cache.get_or_create(
+            &device,
+            &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        );
         let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
             layout: &camera_bind_group_layout,
@@ -473,42 +2077,621 @@ impl DrawContext {
                 resource: camera_buffer.as_entire_binding(),
             }],
         });
+        let scene_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Scene Buffer"),
+            contents: bytemuck::bytes_of(&LightUniform::from(&Light::default())),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+        });
+        let globals_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Globals Buffer"),
+            contents: bytemuck::bytes_of(&CanvasUniform::from(&CanvasUniforms::default())),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+        });
+        // Globals are the canvas uniforms, also usable outside of shadertoy-like shaders
+        let scene_bind_group_layout = bind_group_layout_cache.get_or_create(
+            &device,
+            &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: Self::SCENE_BINDING_LIGHT,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: Self::SCENE_BINDING_GLOBALS,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        );
+        let scene_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Scene bind group"),
+            layout: &scene_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: Self::SCENE_BINDING_LIGHT,
+                    resource: scene_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: Self::SCENE_BINDING_GLOBALS,
+                    resource: globals_buffer.as_entire_binding(),
+                },
+            ],
+        });
+        let texture_bind_group_layout = bind_group_layout_cache.get_or_create(
+            &device,
+            &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        );
+        let default_texture = Texture2D::create(
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+            &Texture2DDescriptor {
+                width: 1,
+                height: 1,
+                rgba: &[u8::MAX; 4],
+                with_mips: false,
+                anisotropy_clamp: 1,
+            },
+        );
+        let default_texture_bind_group = Rc::clone(default_texture.bind_group());
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Pipeline Layout"),
-            bind_group_layouts: &[&camera_bind_group_layout, &transform_bind_group_layout],
+            bind_group_layouts: &[
+                &*camera_bind_group_layout,
+                &transform_bind_group_layout,
+                &*scene_bind_group_layout,
+                &*texture_bind_group_layout,
+            ],
             push_constant_ranges: &[],
         });
-        let depth_texture = device.create_depth_texture(&surface_config, &multisample_config);
+        let depth_texture =
+            device.create_depth_texture(&surface_config, &multisample_config, depth_format);
         let multisample_texture =
             device.create_multisample_texture(&surface_config, &multisample_config);
+        let depth_texture_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let multisample_view = multisample_texture
+            .as_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let gpu_timer = device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+            .then(|| GpuTimer::new(&device, &queue));
+        let offscreen_texture = match surface {
+            Some(_) => None,
+            None => Some(device.create_offscreen_texture(&surface_config)),
+        };
+        let device_lost = Arc::new(Mutex::new(None));
+        let device_lost_flag = Arc::clone(&device_lost);
+        device.set_device_lost_callback(move |reason, message| {
+            // Also called when the device is dropped, e.g. on a normal shutdown
+            if reason == wgpu::DeviceLostReason::Dropped {
+                return;
+            }
+            error!("Device lost ({reason:?}): {message}");
+            *device_lost_flag.lock().unwrap() = Some(format!("{reason:?}: {message}"));
+        });
 
-        Ok(DrawContext {
+        let mut context = DrawContext {
             multisample_config,
+            pipeline_generation: 0,
+            shader_modules: RefCell::new(Vec::new()),
+            anti_aliasing,
+            fxaa: None,
+            render_scale: 1.,
+            upscale: None,
             multisample_texture,
-            _adapter: adapter,
+            depth_texture_view,
+            multisample_view,
+            adapter,
+            depth_format,
+            reverse_z: config.reverse_z,
+            depth_enabled: true,
+            depth_clear_value: None,
+            depth_prepass: false,
+            clear_color: Self::DEFAULT_CLEAR_COLOR,
+            gpu_timer,
+            gpu_timing_enabled: false,
             surface,
+            offscreen_texture,
+            capture_requested: Cell::new(false),
+            frame_capture: RefCell::new(None),
+            device_lost,
+            post_process_source: RefCell::new(None),
+            viewport_cameras: RefCell::new(Vec::new()),
+            bind_group_layout_cache,
+            camera_bind_group_layout,
+            scene_bind_group_layout,
             device,
             queue,
             surface_config,
             camera_buffer,
             camera_bind_group,
+            scene_buffer,
+            globals_buffer,
+            scene_bind_group,
+            default_texture_bind_group,
             transform_bind_group_layout,
+            texture_bind_group_layout,
             vertex_buffer_layout,
+            normal_vertex_buffer_layout,
+            textured_vertex_buffer_layout,
             pipeline_layout,
             depth_texture,
+        };
+        if anti_aliasing == AntiAliasing::Fxaa {
+            context.fxaa = PostProcess::new(&context, FXAA_SHADER)
+                .inspect_err(|err| warn!("Could not create the FXAA pass: {err:#}"))
+                .ok();
+        }
+        context
+    }
+
+    fn check_sample_count(
+        adapter: &wgpu::Adapter,
+        formats: [wgpu::TextureFormat; 2],
+        anti_aliasing: AntiAliasing,
+    ) -> anyhow::Result<()> {
+        let sample_count = anti_aliasing.sample_count();
+        for format in formats {
+            let format_flags = adapter.get_texture_format_features(format).flags;
+            if !format_flags.sample_count_supported(sample_count) {
+                bail!("{sample_count}x MSAA is not supported with the {format:?} format");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_config.present_mode
+    }
+
+    // Whether presenting waits for the vertical blank, which then paces the frames. Always false
+    // without surface.
+    pub fn is_vsync(&self) -> bool {
+        self.surface.is_some()
+            && matches!(
+                self.surface_config.present_mode,
+                wgpu::PresentMode::Fifo
+                    | wgpu::PresentMode::FifoRelaxed
+                    | wgpu::PresentMode::AutoVsync
+            )
+    }
+
+    // Format of the render targets, to use in the color target states of the pipelines
+    pub fn surface_format(&self) -> wgpu::TextureFormat {
+        self.surface_config.format
+    }
+
+    pub fn size(&self) -> Dimensions {
+        Dimensions {
+            width: self.surface_config.width,
+            height: self.surface_config.height,
+        }
+    }
+
+    // Width over height, 1.0 for an empty surface
+    pub fn aspect_ratio(&self) -> f32 {
+        match self.surface_config.height {
+            0 => 1.,
+            height => self.surface_config.width as f32 / height as f32,
+        }
+    }
+
+    pub fn adapter_info(&self) -> wgpu::AdapterInfo {
+        self.adapter.get_info()
+    }
+
+    // Limits of the device, which are the requested ones and not the best the adapter offers
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
+
+    // Supported by the adapter, only TIMESTAMP_QUERY is enabled on the device when available
+    pub fn supports(&self, feature: wgpu::Features) -> bool {
+        self.adapter.features().contains(feature)
+    }
+
+    pub fn depth_format(&self) -> wgpu::TextureFormat {
+        self.depth_format
+    }
+
+    pub fn has_stencil(&self) -> bool {
+        self.depth_format.has_stencil_aspect()
+    }
+
+    // The depth compare is baked in the pipelines, which have to be refreshed after a change, see
+    // Drawable::refresh_pipelines. The depth clear value follows from the next frame.
+    pub fn set_reverse_z(&mut self, reverse_z: bool) {
+        if self.reverse_z != reverse_z {
+            self.reverse_z = reverse_z;
+            self.pipeline_generation += 1;
+        }
+    }
+
+    // See DrawContextConfig::reverse_z
+    pub fn is_reverse_z(&self) -> bool {
+        self.reverse_z
+    }
+
+    pub fn depth_compare(&self) -> wgpu::CompareFunction {
+        match self.reverse_z {
+            true => wgpu::CompareFunction::GreaterEqual,
+            false => wgpu::CompareFunction::LessEqual,
+        }
+    }
+
+    // Without depth attachment, the rendered drawables must have been built without depth test,
+    // see DrawableOptions::depth_test, as their pipeline is otherwise incompatible with the render
+    // pass. The depth attachment is also omitted for scenarios not using depth
+    pub fn set_depth_enabled(&mut self, enabled: bool) {
+        self.depth_enabled = enabled;
+    }
+
+    pub fn is_depth_enabled(&self) -> bool {
+        self.depth_enabled
+    }
+
+    // Opaque drawables are first rendered in a depth only pass, so the fragment shader of the
+    // main pass only runs for visible fragments. Helps scenes with a lot of overdraw and costly
+    // fragment shaders, at the cost of drawing the geometry twice. Like reversed-Z, only applies
+    // to drawables created afterward, and the scenario must implement
+    // DynScenario::render_depth_prepass.
+    pub fn set_depth_prepass(&mut self, depth_prepass: bool) {
+        self.depth_prepass = depth_prepass;
+    }
+
+    pub fn is_depth_prepass(&self) -> bool {
+        self.depth_prepass
+    }
+
+    fn depth_prepass_compare(&self) -> wgpu::CompareFunction {
+        match self.depth_compare() {
+            wgpu::CompareFunction::Less => wgpu::CompareFunction::LessEqual,
+            wgpu::CompareFunction::Greater => wgpu::CompareFunction::GreaterEqual,
+            compare => compare,
+        }
+    }
+
+    // None restores the default value, 1.0 or 0.0 with reversed-Z
+    pub fn set_depth_clear_value(&mut self, value: Option<f32>) {
+        self.depth_clear_value = value;
+    }
+
+    pub fn depth_clear_value(&self) -> f32 {
+        self.depth_clear_value.unwrap_or(match self.reverse_z {
+            true => 0.0,
+            false => 1.0,
         })
     }
 
+    // Once lost, all the GPU resources are invalid, the context and the scenario have to be
+    // created again
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.lock().unwrap().is_some()
+    }
+
+    pub fn device_lost_reason(&self) -> Option<String> {
+        self.device_lost.lock().unwrap().clone()
+    }
+
+    // Used when the scenario has no background of its own, see DynScenario::background
+    pub fn set_clear_color(&mut self, color: wgpu::Color) {
+        self.clear_color = color;
+    }
+
+    pub fn clear_color(&self) -> wgpu::Color {
+        self.clear_color
+    }
+
+    // Highest anisotropy clamp accepted by samplers, 1 when anisotropic filtering is unsupported
+    pub fn max_anisotropy_clamp(&self) -> u16 {
+        let downlevel_flags = self.adapter.get_downlevel_capabilities().flags;
+        if downlevel_flags.contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING) {
+            Self::MAX_ANISOTROPY_CLAMP
+        } else {
+            1
+        }
+    }
+
+    pub fn get_bind_group_layout(
+        &self,
+        entries: &[wgpu::BindGroupLayoutEntry],
+    ) -> Rc<wgpu::BindGroupLayout> {
+        self.bind_group_layout_cache
+            .get_or_create(&self.device, entries)
+    }
+
+    // Same camera, transform and scene groups as pipeline_layout, with another texture group, so
+    // that the pipeline can be used in the same render pass as the drawables
+    pub(crate) fn create_pipeline_layout(
+        &self,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::PipelineLayout {
+        self.device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Pipeline Layout"),
+                bind_group_layouts: &[
+                    &*self.camera_bind_group_layout,
+                    &self.transform_bind_group_layout,
+                    &*self.scene_bind_group_layout,
+                    texture_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            })
+    }
+
+    pub fn create_shader_module(
+        &self,
+        label: &str,
+        wgsl_source: &str,
+    ) -> anyhow::Result<Rc<wgpu::ShaderModule>> {
+        let shader_module =
+            self.with_validation_scope(&format!("Failed to compile shader \"{label}\""), || {
+                self.device
+                    .create_shader_module(wgpu::ShaderModuleDescriptor {
+                        label: Some(label),
+                        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+                    })
+            })?;
+        let shader_module = Rc::new(shader_module);
+        let mut shader_modules = self.shader_modules.borrow_mut();
+        shader_modules.retain(|module| module.strong_count() > 0);
+        shader_modules.push(Rc::downgrade(&shader_module));
+        Ok(shader_module)
+    }
+
+    fn shared_shader_module(&self, module: &wgpu::ShaderModule) -> Option<Rc<wgpu::ShaderModule>> {
+        self.shader_modules
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|shared| **shared == *module)
+    }
+
+    pub(crate) fn pipeline_generation(&self) -> u32 {
+        self.pipeline_generation
+    }
+
+    // Validation errors are reported right away with wgpu-core backends (native, WebGL). With
+    // browser WebGPU the error scope only resolves later, the error is then logged.
+    pub(crate) fn with_validation_scope<T>(
+        &self,
+        error_message: &str,
+        create: impl FnOnce() -> T,
+    ) -> anyhow::Result<T> {
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let created = create();
+        let mut error_scope = Box::pin(self.device.pop_error_scope());
+        let mut task_context = std::task::Context::from_waker(std::task::Waker::noop());
+        match error_scope.as_mut().poll(&mut task_context) {
+            Poll::Ready(Some(err)) => {
+                return Err(anyhow!("{error_message}: {err}"));
+            }
+            Poll::Ready(None) => {}
+            Poll::Pending => {
+                #[cfg(target_arch = "wasm32")]
+                {
+                    let error_message = error_message.to_owned();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        if let Some(err) = error_scope.await {
+                            log::error!("{error_message}: {err}");
+                        }
+                    });
+                }
+            }
+        }
+        Ok(created)
+    }
+
+    // The surface must be copyable, in a format the captured frames can be decoded from
+    pub fn is_frame_capture_supported(&self) -> bool {
+        self.surface_config
+            .usage
+            .contains(wgpu::TextureUsages::COPY_SRC)
+            && FrameCapture::is_format_supported(self.surface_config.format)
+    }
+
+    // The next rendered frame is copied, and can be retrieved later with take_captured_frame
+    pub fn request_frame_capture(&self) {
+        if !self.is_frame_capture_supported() {
+            warn!(
+                "Frame capture requested but not supported by the {:?} surface",
+                self.surface_config.format
+            );
+            return;
+        }
+        self.capture_requested.set(true);
+    }
+
+    // Renders a frame of the scenario and waits for its readback. Blocking, so not usable with the
+    // browser WebGPU where the readback completes later, request_frame_capture and
+    // take_captured_frame have to be used instead.
+    pub fn capture_frame<T: Scenario>(&self, scene: &mut T) -> anyhow::Result<image::RgbaImage> {
+        if !self.is_frame_capture_supported() {
+            bail!("Frame capture is not supported by the surface");
+        }
+        self.capture_requested.set(true);
+        let render_result = self.render_scene(scene);
+        // Not left pending if the frame was skipped
+        self.capture_requested.set(false);
+        render_result?;
+        self.device.poll(wgpu::Maintain::Wait);
+        let frame = self
+            .take_captured_frame()?
+            .context("The frame was not captured")?;
+        Ok(frame.into_image())
+    }
+
+    // Returns the captured frame once the GPU readback is done, usually a frame after the request,
+    // or an error if it could not be decoded
+    pub fn take_captured_frame(&self) -> anyhow::Result<Option<CapturedFrame>> {
+        if self.frame_capture.borrow().is_none() {
+            return Ok(None);
+        }
+        self.device.poll(wgpu::Maintain::Poll);
+        let mut frame_capture = self.frame_capture.borrow_mut();
+        if !frame_capture.as_ref().is_some_and(FrameCapture::is_ready) {
+            return Ok(None);
+        }
+        frame_capture.take().map(FrameCapture::read).transpose()
+    }
+
+    pub fn is_gpu_timing_supported(&self) -> bool {
+        self.gpu_timer.is_some()
+    }
+
+    pub fn set_gpu_timing_enabled(&mut self, enabled: bool) {
+        if enabled && self.gpu_timer.is_none() {
+            warn!("GPU timing requested but TIMESTAMP_QUERY feature is not available");
+        }
+        self.gpu_timing_enabled = enabled;
+    }
+
+    pub fn is_gpu_timing_enabled(&self) -> bool {
+        self.gpu_timing_enabled && self.gpu_timer.is_some()
+    }
+
+    pub fn timestamp_period(&self) -> Option<f32> {
+        self.gpu_timer.as_ref().map(|timer| timer.timestamp_period)
+    }
+
+    pub fn render_pass_gpu_duration(&self) -> Option<Duration> {
+        self.gpu_timer
+            .as_ref()
+            .filter(|_| self.gpu_timing_enabled)
+            .and_then(|timer| timer.last_duration.get())
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.surface.is_none()
+    }
+
+    pub fn offscreen_texture(&self) -> Option<&wgpu::Texture> {
+        self.offscreen_texture.as_ref()
+    }
+
+    // A zero size, sent by some platforms when minimizing, cannot configure a surface and is
+    // ignored
     pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            debug!("Ignoring resize to {width}x{height}");
+            return;
+        }
         self.surface_config.width = width;
         self.surface_config.height = height;
-        self.surface.configure(&self.device, &self.surface_config);
-        self.depth_texture = self
-            .device
-            .create_depth_texture(&self.surface_config, &self.multisample_config);
+        match self.surface {
+            Some(ref surface) => surface.configure(&self.device, &self.surface_config),
+            None => {
+                self.offscreen_texture =
+                    Some(self.device.create_offscreen_texture(&self.surface_config))
+            }
+        }
+        self.recreate_render_textures();
+    }
+
+    // The sample count is baked in the pipelines, which have to be refreshed after a MSAA change,
+    // see Drawable::refresh_pipelines. The multisample and depth textures are created again. The
+    // FXAA pass is only applied to scenes without their own post-process.
+    pub fn set_anti_aliasing(&mut self, anti_aliasing: AntiAliasing) -> anyhow::Result<()> {
+        let formats = [self.surface_config.format, self.depth_format];
+        Self::check_sample_count(&self.adapter, formats, anti_aliasing)?;
+        self.fxaa = match anti_aliasing {
+            AntiAliasing::Fxaa => Some(PostProcess::new(self, FXAA_SHADER)?),
+            _ => None,
+        };
+        if anti_aliasing.sample_count() != self.anti_aliasing.sample_count() {
+            self.multisample_config = anti_aliasing.multisample_config();
+            self.recreate_render_textures();
+            self.pipeline_generation += 1;
+        }
+        self.anti_aliasing = anti_aliasing;
+        Ok(())
+    }
+
+    pub fn anti_aliasing(&self) -> AntiAliasing {
+        self.anti_aliasing
+    }
+
+    // The scene is rendered in a texture of the surface size multiplied by the scale, clamped
+    // between 0.25 and 2, then stretched to the surface with a bilinear filter. Below 1, it trades
+    // sharpness for fill rate, above it is a costly supersampling. Overlays are still rendered at
+    // the surface size. Like the FXAA pass, the upscale is only applied to scenes without their own
+    // post-process, which then do the upscale themselves. Color targets created before a change
+    // must be created again.
+    pub fn set_render_scale(&mut self, render_scale: f32) -> anyhow::Result<()> {
+        let render_scale = render_scale.clamp(Self::MIN_RENDER_SCALE, Self::MAX_RENDER_SCALE);
+        self.upscale = match render_scale == 1. {
+            true => None,
+            false => Some(PostProcess::new(self, UPSCALE_SHADER)?),
+        };
+        self.render_scale = render_scale;
+        self.recreate_render_textures();
+        Ok(())
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    // Size of the textures the scene is rendered in, see set_render_scale
+    pub fn render_size(&self) -> Dimensions {
+        let scale = |length: u32| ((length as f32 * self.render_scale).round() as u32).max(1);
+        Dimensions {
+            width: scale(self.surface_config.width),
+            height: scale(self.surface_config.height),
+        }
+    }
+
+    fn recreate_render_textures(&mut self) {
+        let Dimensions { width, height } = self.render_size();
+        let render_config = wgpu::SurfaceConfiguration {
+            width,
+            height,
+            ..self.surface_config.clone()
+        };
+        self.depth_texture = self.device.create_depth_texture(
+            &render_config,
+            &self.multisample_config,
+            self.depth_format,
+        );
         self.multisample_texture = self
             .device
-            .create_multisample_texture(&self.surface_config, &self.multisample_config);
+            .create_multisample_texture(&render_config, &self.multisample_config);
+        self.depth_texture_view = self
+            .depth_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.multisample_view = self
+            .multisample_texture
+            .as_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        self.post_process_source.get_mut().take();
     }
 
     pub fn set_projection(&self, transform: impl AsRef<[[f32; 4]; 4]>) {
@@ -520,63 +2703,571 @@ impl DrawContext {
         );
     }
 
-    pub fn render_scene<T: Scenario>(&self, scene: &T) -> anyhow::Result<()> {
-        let depth_texture_view = self
-            .depth_texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        let displayed_texture = self.surface.get_current_texture()?;
-        let displayed_view = displayed_texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        let (pass_view, pass_resolve_target) = if self.multisample_config.is_multisample_enabled() {
-            let multisample_texture = self
-                .multisample_texture
+    pub fn set_light(&self, light: &Light) {
+        self.queue.write_buffer(
+            &self.scene_buffer,
+            0,
+            bytemuck::bytes_of(&LightUniform::from(light)),
+        );
+    }
+
+    pub fn set_canvas_uniforms(&self, uniforms: &CanvasUniforms) {
+        self.queue.write_buffer(
+            &self.globals_buffer,
+            0,
+            bytemuck::bytes_of(&CanvasUniform::from(uniforms)),
+        );
+    }
+
+    // Extra color targets must be recreated by the caller when the context is resized
+    // or its render scale changed
+    pub fn create_color_target(&self, format: wgpu::TextureFormat) -> ColorTarget {
+        let Dimensions { width, height } = self.render_size();
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Color Target Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            view_formats: &[],
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        });
+        let multisample_texture = self.multisample_config.is_multisample_enabled().then(|| {
+            self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Color Target Multisample Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: self.multisample_config.get_multisample_count(),
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                view_formats: &[],
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            })
+        });
+        ColorTarget {
+            texture,
+            multisample_texture,
+        }
+    }
+
+    pub fn render_scene<T: Scenario>(&self, scene: &mut T) -> anyhow::Result<RenderStats> {
+        self.render_scene_impl(&mut [scene], RenderParams::default())
+    }
+
+    // The surface is bound at location 0, extra targets follow at location 1, 2, etc.
+    pub fn render_scene_with_targets<T: Scenario>(
+        &self,
+        scene: &mut T,
+        extra_targets: &[&ColorTarget],
+    ) -> anyhow::Result<RenderStats> {
+        self.render_scene_impl(
+            &mut [scene],
+            RenderParams {
+                extra_targets,
+                ..Default::default()
+            },
+        )
+    }
+
+    // With LoadOp::Load, the depth buffer is loaded too, so new drawables are still depth tested
+    // against what was previously drawn. Previous color content is only kept between frames when
+    // rendering in the multisample or offscreen texture, the swapchain texture is not preserved.
+    pub fn render_scene_with_load_op<T: Scenario>(
+        &self,
+        scene: &mut T,
+        color_load_op: wgpu::LoadOp<wgpu::Color>,
+    ) -> anyhow::Result<RenderStats> {
+        self.render_scene_impl(
+            &mut [scene],
+            RenderParams {
+                color_load_op: Some(color_load_op),
+                ..Default::default()
+            },
+        )
+    }
+
+    // The overlay is rendered after the scene and its own overlay, on the final single-sampled
+    // target
+    pub fn render_scene_with_overlay<T: Scenario>(
+        &self,
+        scene: &mut T,
+        overlay: &mut dyn RenderOverlay,
+    ) -> anyhow::Result<RenderStats> {
+        self.render_scene_impl(
+            &mut [scene],
+            RenderParams {
+                overlay: Some(overlay),
+                ..Default::default()
+            },
+        )
+    }
+
+    // The scene is rendered in an intermediate texture, which the post-process samples to draw
+    // the final frame. Overlays are drawn after the post-process, so are not affected by it.
+    // Takes precedence over DynScenario::post_process.
+    pub fn render_scene_with_post<T: Scenario>(
+        &self,
+        scene: &mut T,
+        post_process: &PostProcess,
+    ) -> anyhow::Result<RenderStats> {
+        self.render_scene_impl(
+            &mut [scene],
+            RenderParams {
+                post_process: Some(post_process),
+                ..Default::default()
+            },
+        )
+    }
+
+    // Split-screen rendering, the scenario being rendered once per viewport in the same render
+    // pass. The camera set with set_projection is not used. Takes precedence over
+    // DynScenario::viewports.
+    pub fn render_scene_with_viewports<T: Scenario>(
+        &self,
+        scene: &mut T,
+        viewports: &[Viewport],
+    ) -> anyhow::Result<RenderStats> {
+        self.render_scene_impl(
+            &mut [scene],
+            RenderParams {
+                viewports: Some(viewports),
+                ..Default::default()
+            },
+        )
+    }
+
+    // Layers are rendered in order in the same frame, e.g. a 3D scene then a 2D HUD. Only the
+    // first layer clears the target, following ones are drawn over it. The depth buffer is cleared
+    // before each layer, so a layer is only depth tested against its own drawables. The background,
+    // post-process and viewports of the first layer are used, viewports only applying to it, while
+    // the overlays of every layer are rendered after the post-process, in the same order.
+    // Layers are rendered with the camera of set_projection, unless they have their own with
+    // DynScenario::layer_camera_matrix, as a HUD must not be projected by the camera of the 3D
+    // scene.
+    pub fn render_layers(
+        &self,
+        layers: &mut [&mut dyn DynScenario],
+    ) -> anyhow::Result<RenderStats> {
+        if layers.is_empty() {
+            bail!("At least one layer is needed to render a frame");
+        }
+        self.render_scene_impl(layers, RenderParams::default())
+    }
+
+    // Written before the frame is submitted, one buffer per viewport or layer camera as queue
+    // writes all happen before the render passes
+    fn write_viewport_cameras(&self, camera_matrices: &[[[f32; 4]; 4]]) {
+        let mut viewport_cameras = self.viewport_cameras.borrow_mut();
+        while viewport_cameras.len() < camera_matrices.len() {
+            let buffer = self.device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("Viewport Camera Buffer"),
+                contents: bytemuck::cast_slice(&M4X4_ID_UNIFORM),
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            });
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Viewport Camera bind group"),
+                layout: &self.camera_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+            viewport_cameras.push(ViewportCamera { buffer, bind_group });
+        }
+        for (camera_matrix, viewport_camera) in camera_matrices.iter().zip(viewport_cameras.iter())
+        {
+            self.queue.write_buffer(
+                &viewport_camera.buffer,
+                0,
+                bytemuck::cast_slice(camera_matrix),
+            );
+        }
+    }
+
+    fn post_process_source(&self) -> Rc<PostProcessSource> {
+        let mut post_process_source = self.post_process_source.borrow_mut();
+        Rc::clone(post_process_source.get_or_insert_with(|| Rc::new(PostProcessSource::new(self))))
+    }
+
+    fn render_scene_impl(
+        &self,
+        layers: &mut [&mut dyn DynScenario],
+        params: RenderParams,
+    ) -> anyhow::Result<RenderStats> {
+        let RenderParams {
+            extra_targets,
+            color_load_op,
+            overlay,
+            post_process,
+            viewports,
+        } = params;
+        let mut color_load_op = color_load_op.unwrap_or_else(|| {
+            let background = layers.first().and_then(|layer| layer.background());
+            wgpu::LoadOp::Clear(background.unwrap_or(self.clear_color))
+        });
+        let (displayed_texture, displayed_view) = match self.surface {
+            Some(ref surface) => {
+                let surface_texture = match surface.get_current_texture() {
+                    Ok(surface_texture) => surface_texture,
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                        debug!("Surface lost or outdated, reconfiguring and skipping frame");
+                        surface.configure(&self.device, &self.surface_config);
+                        return Ok(RenderStats::default());
+                    }
+                    Err(wgpu::SurfaceError::Timeout) => {
+                        warn!("Timeout when acquiring surface texture, skipping frame");
+                        return Ok(RenderStats::default());
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+                let view = surface_texture
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                (Some(surface_texture), view)
+            }
+            None => {
+                let view = self
+                    .offscreen_texture
+                    .as_ref()
+                    .expect("Without surface, an offscreen texture should be present")
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                (None, view)
+            }
+        };
+        let multisample_view = self.multisample_config.is_multisample_enabled().then(|| {
+            self.multisample_view
                 .as_ref()
-                .expect("When multisample_enabled is at true, this optional should not be empty");
-            let multisample_view =
-                multisample_texture.create_view(&wgpu::TextureViewDescriptor::default());
-            (multisample_view, Some(&displayed_view))
-        } else {
-            (displayed_view, None)
+                .expect("When multisample_enabled is at true, this optional should not be empty")
+        });
+        let post_process = post_process
+            .or_else(|| layers.first().and_then(|layer| layer.post_process()))
+            .or(self.fxaa.as_ref())
+            .or(self.upscale.as_ref());
+        // Copied, as the scenarios are borrowed mutably for their overlay
+        let viewports = viewports
+            .or_else(|| layers.first().and_then(|layer| layer.viewports()))
+            .map(<[Viewport]>::to_vec);
+        // Layer cameras are stored after the viewport ones
+        let viewport_count = viewports.as_ref().map_or(0, Vec::len);
+        let layer_camera_matrices: Vec<_> = layers
+            .iter()
+            .map(|layer| layer.layer_camera_matrix())
+            .collect();
+        let camera_matrices: Vec<[[f32; 4]; 4]> = viewports
+            .iter()
+            .flatten()
+            .map(|viewport| viewport.camera_matrix)
+            .chain(layer_camera_matrices.iter().flatten().map(|&m| m.into()))
+            .collect();
+        if !camera_matrices.is_empty() {
+            self.write_viewport_cameras(&camera_matrices);
+        }
+        let viewport_cameras = self.viewport_cameras.borrow();
+        let mut layer_camera_index = viewport_count;
+        let render_size = self.render_size();
+        let post_process_source = post_process.map(|_| self.post_process_source());
+        let scene_view = post_process_source
+            .as_ref()
+            .map_or(&displayed_view, |source| &source.view);
+        let (pass_view, pass_resolve_target) = match multisample_view {
+            Some(multisample_view) => (multisample_view, Some(scene_view)),
+            None => (scene_view, None),
+        };
+        let extra_target_views: Vec<_> = extra_targets
+            .iter()
+            .map(|target| target.create_views())
+            .collect();
+        let (mut extra_load_op, mut depth_load_op, mut stencil_load_op) = match color_load_op {
+            wgpu::LoadOp::Clear(_) => (
+                wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                wgpu::LoadOp::Clear(self.depth_clear_value()),
+                wgpu::LoadOp::Clear(0),
+            ),
+            wgpu::LoadOp::Load => (wgpu::LoadOp::Load, wgpu::LoadOp::Load, wgpu::LoadOp::Load),
         };
+        let gpu_timer = self.gpu_timer.as_ref().filter(|_| self.gpu_timing_enabled);
+        if let Some(gpu_timer) = gpu_timer {
+            gpu_timer.collect(&self.device);
+        }
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Command Encoder"),
             });
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render pass"),
-            timestamp_writes: None,
-            occlusion_query_set: None,
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &pass_view,
+        let mut render_stats = RenderStats::default();
+        for (index, layer) in layers.iter().enumerate() {
+            let layer: &dyn DynScenario = &**layer;
+            if index > 0 {
+                color_load_op = wgpu::LoadOp::Load;
+                extra_load_op = wgpu::LoadOp::Load;
+                depth_load_op = wgpu::LoadOp::Clear(self.depth_clear_value());
+                stencil_load_op = wgpu::LoadOp::Clear(0);
+            }
+            let layer_viewports = viewports.as_deref().filter(|_| index == 0);
+            let camera_bind_group = match layer_camera_matrices[index] {
+                Some(_) => {
+                    layer_camera_index += 1;
+                    &viewport_cameras[layer_camera_index - 1].bind_group
+                }
+                None => &self.camera_bind_group,
+            };
+            let mut color_attachments = vec![Some(wgpu::RenderPassColorAttachment {
+                view: pass_view,
                 resolve_target: pass_resolve_target,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.5,
-                        b: 0.5,
-                        a: 1.0,
-                    }),
+                    load: color_load_op,
                     store: wgpu::StoreOp::Store,
                 },
-            })],
-            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                view: &depth_texture_view,
-                depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
-                    store: wgpu::StoreOp::Store,
-                }),
-                stencil_ops: None,
-            }),
-        });
-        render_pass.set_bind_group(Self::BIND_GROUP_INDEX_CAMERA, &self.camera_bind_group, &[]);
-        scene.render(&mut render_pass);
-
-        drop(render_pass);
+            })];
+            color_attachments.extend(extra_target_views.iter().map(|(view, resolve_target)| {
+                Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: resolve_target.as_ref(),
+                    ops: wgpu::Operations {
+                        load: extra_load_op,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            }));
+            let depth_enabled = self.depth_enabled && layer.uses_depth();
+            if depth_enabled && self.depth_prepass {
+                let mut depth_prepass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Depth prepass"),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                    color_attachments: &[],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth_texture_view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: depth_load_op,
+                            store: wgpu::StoreOp::Store,
+                        }),
+                        stencil_ops: self.has_stencil().then_some(wgpu::Operations {
+                            load: stencil_load_op,
+                            store: wgpu::StoreOp::Store,
+                        }),
+                    }),
+                });
+                depth_prepass.set_bind_group(Self::BIND_GROUP_INDEX_CAMERA, camera_bind_group, &[]);
+                depth_prepass.set_bind_group(
+                    Self::BIND_GROUP_INDEX_SCENE,
+                    &self.scene_bind_group,
+                    &[],
+                );
+                render_viewports(
+                    &mut depth_prepass,
+                    layer_viewports,
+                    &viewport_cameras,
+                    render_size,
+                    |render_pass| layer.render_depth_prepass(render_pass),
+                );
+                drop(depth_prepass);
+                depth_load_op = wgpu::LoadOp::Load;
+                stencil_load_op = wgpu::LoadOp::Load;
+            }
+            // GPU timing only measures the first layer, the query set being written once per frame
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render pass"),
+                timestamp_writes: gpu_timer
+                    .filter(|_| index == 0)
+                    .map(GpuTimer::timestamp_writes),
+                occlusion_query_set: None,
+                color_attachments: &color_attachments,
+                depth_stencil_attachment: depth_enabled.then_some(
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth_texture_view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: depth_load_op,
+                            store: wgpu::StoreOp::Store,
+                        }),
+                        stencil_ops: self.has_stencil().then_some(wgpu::Operations {
+                            load: stencil_load_op,
+                            store: wgpu::StoreOp::Store,
+                        }),
+                    },
+                ),
+            });
+            render_pass.set_bind_group(Self::BIND_GROUP_INDEX_CAMERA, camera_bind_group, &[]);
+            render_pass.set_bind_group(Self::BIND_GROUP_INDEX_SCENE, &self.scene_bind_group, &[]);
+            render_viewports(
+                &mut render_pass,
+                layer_viewports,
+                &viewport_cameras,
+                render_size,
+                |render_pass| layer.render(render_pass, &mut render_stats),
+            );
+        }
+        if let (Some(post_process), Some(source)) = (post_process, &post_process_source) {
+            post_process.render(&mut encoder, source, &displayed_view);
+        }
+        for layer in layers.iter_mut() {
+            if let Some(layer_overlay) = layer.overlay() {
+                layer_overlay.render_overlay(self, &mut encoder, &displayed_view);
+            }
+        }
+        if let Some(overlay) = overlay {
+            overlay.render_overlay(self, &mut encoder, &displayed_view);
+        }
+        let frame_capture = self
+            .capture_requested
+            .take()
+            .then(|| {
+                displayed_texture
+                    .as_ref()
+                    .map(|surface_texture| &surface_texture.texture)
+                    .or(self.offscreen_texture.as_ref())
+            })
+            .flatten()
+            .map(|texture| FrameCapture::record(&self.device, &mut encoder, texture));
+        let gpu_timer_readback = gpu_timer.filter(|timer| timer.resolve(&mut encoder));
         let command_buffers = std::iter::once(encoder.finish());
         self.queue.submit(command_buffers);
-        displayed_texture.present();
-        Ok(())
+        if let Some(gpu_timer) = gpu_timer_readback {
+            gpu_timer.map_readback();
+        }
+        if let Some(frame_capture) = frame_capture {
+            frame_capture.map_readback();
+            self.frame_capture.replace(Some(frame_capture));
+        }
+        if let Some(displayed_texture) = displayed_texture {
+            displayed_texture.present();
+        }
+        Ok(render_stats)
+    }
+}
+
+// Renders once without viewports, otherwise once per viewport with its camera
+fn render_viewports<'pass>(
+    render_pass: &mut wgpu::RenderPass<'pass>,
+    viewports: Option<&[Viewport]>,
+    viewport_cameras: &'pass [ViewportCamera],
+    render_size: Dimensions,
+    mut render: impl FnMut(&mut wgpu::RenderPass<'pass>),
+) {
+    let Some(viewports) = viewports else {
+        render(render_pass);
+        return;
+    };
+    for (viewport, viewport_camera) in viewports.iter().zip(viewport_cameras) {
+        let [x, y, width, height] = viewport.rect.to_pixels(render_size);
+        render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0., 1.);
+        render_pass.set_scissor_rect(x, y, width, height);
+        render_pass.set_bind_group(
+            DrawContext::BIND_GROUP_INDEX_CAMERA,
+            &viewport_camera.bind_group,
+            &[],
+        );
+        render(render_pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{cube, Object3D};
+    use crate::scenario::UpdateInterval;
+
+    const FLAT_SHADER: &str = "
+        @vertex
+        fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+            return vec4<f32>(position * 0.5, 1.0);
+        }
+        @fragment
+        fn fs_main() -> @location(0) vec4<f32> {
+            return vec4<f32>(1.0);
+        }
+    ";
+
+    struct CubeScenario(Object3D);
+
+    impl Scenario for CubeScenario {
+        fn new(context: &DrawContext) -> anyhow::Result<Self> {
+            let module = context.create_shader_module("Flat Shader", FLAT_SHADER)?;
+            let vertex_state = wgpu::VertexState {
+                module: &module,
+                entry_point: None,
+                compilation_options: Default::default(),
+                buffers: std::slice::from_ref(&context.vertex_buffer_layout),
+            };
+            let fragment_state = wgpu::FragmentState {
+                module: &module,
+                entry_point: None,
+                compilation_options: Default::default(),
+                targets: &[Some(context.surface_format().into())],
+            };
+            Ok(CubeScenario(cube::create_cube(
+                context,
+                vertex_state,
+                fragment_state,
+            )))
+        }
+    }
+
+    impl DynScenario for CubeScenario {
+        fn update(&mut self, _context: &DrawContext, _update_interval: &UpdateInterval) {}
+        fn render<'drawable>(
+            &'drawable self,
+            render_pass: &mut wgpu::RenderPass<'drawable>,
+            _render_stats: &mut RenderStats,
+        ) {
+            self.0.as_ref().render(render_pass);
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a GPU adapter"]
+    fn refreshed_pipelines_follow_the_depth_and_sample_settings() {
+        let mut context = pollster::block_on(DrawContext::new_headless(64, 64))
+            .expect("No adapter for the headless context");
+        let mut scenario = CubeScenario::new(&context).unwrap();
+        let anti_aliasing = match context.anti_aliasing().sample_count() {
+            1 => AntiAliasing::Msaa4,
+            _ => AntiAliasing::None,
+        };
+        context.set_anti_aliasing(anti_aliasing).unwrap();
+        context.set_reverse_z(!context.is_reverse_z());
+        scenario.0.as_mut().refresh_pipelines(&context).unwrap();
+        context
+            .device
+            .push_error_scope(wgpu::ErrorFilter::Validation);
+        context.render_scene(&mut scenario).unwrap();
+        let error = pollster::block_on(context.device.pop_error_scope());
+        assert!(error.is_none(), "{error:?}");
+    }
+
+    #[test]
+    fn captured_frames_are_decoded_to_rgba8() {
+        let bgra = FrameCapture::decode(wgpu::TextureFormat::Bgra8Unorm, vec![1, 2, 3, 4]).unwrap();
+        assert_eq!(bgra, [3, 2, 1, 4]);
+        let packed: u32 = 0x3ff | (0x200 << 10) | (3 << 30);
+        let rgb10a2 = FrameCapture::decode(
+            wgpu::TextureFormat::Rgb10a2Unorm,
+            packed.to_le_bytes().to_vec(),
+        )
+        .unwrap();
+        assert_eq!(rgb10a2, [255, 128, 0, 255]);
+        assert!(FrameCapture::decode(wgpu::TextureFormat::R32Float, vec![0; 4]).is_err());
+    }
+
+    #[test]
+    fn viewport_rect_is_clamped_in_the_target() {
+        let rect = ViewportRect {
+            x: 0.5,
+            y: 0.9,
+            width: 0.5,
+            height: 0.5,
+        };
+        let size = |width, height| Dimensions { width, height };
+        assert_eq!(rect.to_pixels(size(100, 10)), [50, 9, 50, 1]);
+        assert_eq!(rect.to_pixels(size(0, 0)), [0, 0, 0, 0]);
+        assert_eq!(ViewportRect::FULL.to_pixels(size(0, 4)), [0, 0, 0, 4]);
     }
 }
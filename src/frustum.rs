@@ -0,0 +1,134 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{InnerSpace, Matrix4, Point3, Vector3, Vector4};
+
+// Points p of the plane verify dot(normal, p) + d = 0, the normal being normalized
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub d: f32,
+}
+
+impl Plane {
+    fn from_coefficients(coefficients: Vector4<f32>) -> Self {
+        let normal = coefficients.truncate();
+        let length = normal.magnitude();
+        Plane {
+            normal: normal / length,
+            d: coefficients.w / length,
+        }
+    }
+
+    // Signed, positive on the side the normal points to
+    pub fn distance(&self, point: Point3<f32>) -> f32 {
+        self.normal.dot(Vector3::new(point.x, point.y, point.z)) + self.d
+    }
+}
+
+// Planes are extracted from the camera matrix with the Gribb-Hartmann method, with normals
+// pointing inside the frustum. They are in the order left, right, bottom, top, near and far, near
+// and far being swapped with a reversed-Z camera matrix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    planes: [Plane; 6],
+}
+
+impl Frustum {
+    // WebGPU clip space: -w <= x <= w, -w <= y <= w, 0 <= z <= w
+    pub fn from_camera_matrix(camera_matrix: &Matrix4<f32>) -> Self {
+        let m = camera_matrix;
+        let row = |i: usize| Vector4::new(m.x[i], m.y[i], m.z[i], m.w[i]);
+        let (row_x, row_y, row_z, row_w) = (row(0), row(1), row(2), row(3));
+        Frustum {
+            planes: [
+                Plane::from_coefficients(row_w + row_x),
+                Plane::from_coefficients(row_w - row_x),
+                Plane::from_coefficients(row_w + row_y),
+                Plane::from_coefficients(row_w - row_y),
+                Plane::from_coefficients(row_z),
+                Plane::from_coefficients(row_w - row_z),
+            ],
+        }
+    }
+
+    pub fn intersects_sphere(&self, center: Point3<f32>, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.distance(center) >= -radius)
+    }
+
+    pub fn planes(&self) -> &[Plane; 6] {
+        &self.planes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cameras::{Camera, PerspectiveConfig};
+
+    const NEAR_PLANE: usize = 4;
+
+    // Eye at (0, 0, -5), looking toward the origin
+    fn default_camera(reverse_z: bool) -> Camera {
+        let mut camera = Camera::from(PerspectiveConfig::default());
+        camera.set_reverse_z(reverse_z);
+        camera
+    }
+
+    #[test]
+    fn object_behind_the_camera_is_culled() {
+        let camera = default_camera(false);
+        let frustum = Frustum::from_camera_matrix(&camera.get_camera_matrix());
+        assert!(frustum.intersects_sphere(Point3::new(0., 0., 0.), 1.));
+        assert!(!frustum.intersects_sphere(Point3::new(0., 0., -20.), 1.));
+    }
+
+    #[test]
+    fn frustum_center_is_inside_all_planes() {
+        let config = PerspectiveConfig::default();
+        let center = Point3::new(0., 0., -5. + (config.near + config.far) / 2.);
+        for reverse_z in [false, true] {
+            for plane in default_camera(reverse_z).frustum_planes() {
+                assert!(
+                    plane.distance(center) > 0.,
+                    "reverse_z: {reverse_z}, {plane:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn point_behind_the_eye_is_outside_the_near_plane() {
+        let behind = Point3::new(0., 0., -1_000.);
+        for reverse_z in [false, true] {
+            let planes = default_camera(reverse_z).frustum_planes();
+            assert!(
+                planes[NEAR_PLANE].distance(behind) < 0.,
+                "reverse_z: {reverse_z}"
+            );
+        }
+    }
+}
@@ -0,0 +1,140 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use winit::event::WindowEvent;
+use winit::window::Window;
+
+use crate::draw_context::{DrawContext, RenderOverlay};
+use crate::scenario::Scenario;
+
+struct GuiFrame {
+    paint_jobs: Vec<egui::ClippedPrimitive>,
+    textures_delta: egui::TexturesDelta,
+    pixels_per_point: f32,
+}
+
+pub struct EguiOverlay {
+    context: egui::Context,
+    state: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+    frame: Option<GuiFrame>,
+}
+
+impl EguiOverlay {
+    pub fn new(window: &Window, draw_context: &DrawContext) -> Self {
+        let context = egui::Context::default();
+        let state = egui_winit::State::new(
+            context.clone(),
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            None,
+            Some(draw_context.device.limits().max_texture_dimension_2d as usize),
+        );
+        let renderer = egui_wgpu::Renderer::new(
+            &draw_context.device,
+            draw_context.surface_format(),
+            None,
+            1,
+            false,
+        );
+        EguiOverlay {
+            context,
+            state,
+            renderer,
+            frame: None,
+        }
+    }
+
+    // Returns true if egui consumed the event, and so it should not be forwarded to the scene
+    pub fn window_event(&mut self, window: &Window, event: &WindowEvent) -> bool {
+        self.state.on_window_event(window, event).consumed
+    }
+
+    pub fn prepare<S: Scenario>(&mut self, window: &Window, scenario: &mut S) {
+        let raw_input = self.state.take_egui_input(window);
+        let output = self.context.run(raw_input, |ctx| scenario.on_gui(ctx));
+        self.state
+            .handle_platform_output(window, output.platform_output);
+        let paint_jobs = self
+            .context
+            .tessellate(output.shapes, output.pixels_per_point);
+        self.frame = Some(GuiFrame {
+            paint_jobs,
+            textures_delta: output.textures_delta,
+            pixels_per_point: output.pixels_per_point,
+        });
+    }
+}
+
+impl RenderOverlay for EguiOverlay {
+    fn render_overlay(
+        &mut self,
+        context: &DrawContext,
+        encoder: &mut wgpu::CommandEncoder,
+        target_view: &wgpu::TextureView,
+    ) {
+        let Some(frame) = self.frame.take() else {
+            return;
+        };
+        let screen_descriptor = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [context.size().width, context.size().height],
+            pixels_per_point: frame.pixels_per_point,
+        };
+        for (id, image_delta) in &frame.textures_delta.set {
+            self.renderer
+                .update_texture(&context.device, &context.queue, *id, image_delta);
+        }
+        let command_buffers = self.renderer.update_buffers(
+            &context.device,
+            &context.queue,
+            encoder,
+            &frame.paint_jobs,
+            &screen_descriptor,
+        );
+        context.queue.submit(command_buffers);
+        let mut render_pass = encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("egui"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            })
+            .forget_lifetime();
+        self.renderer
+            .render(&mut render_pass, &frame.paint_jobs, &screen_descriptor);
+        drop(render_pass);
+        for id in &frame.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::collections::BTreeSet;
+
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+// Keyboard snapshot given to the scenario updates. Edges are kept until the next update, so no
+// key press is missed when a frame runs no update, e.g. with a fixed update rate
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    held_keys: BTreeSet<KeyCode>,
+    just_pressed_keys: BTreeSet<KeyCode>,
+    just_released_keys: BTreeSet<KeyCode>,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn is_pressed(&self, key: KeyCode) -> bool {
+        self.held_keys.contains(&key)
+    }
+
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.just_pressed_keys.contains(&key)
+    }
+
+    pub fn just_released(&self, key: KeyCode) -> bool {
+        self.just_released_keys.contains(&key)
+    }
+
+    pub fn held_keys(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.held_keys.iter().copied()
+    }
+
+    pub fn keyboard_event_listener(&mut self, input: &KeyEvent) {
+        let PhysicalKey::Code(key_code) = input.physical_key else {
+            return;
+        };
+        match input.state {
+            ElementState::Pressed => {
+                if self.held_keys.insert(key_code) {
+                    self.just_pressed_keys.insert(key_code);
+                }
+            }
+            ElementState::Released => {
+                if self.held_keys.remove(&key_code) {
+                    self.just_released_keys.insert(key_code);
+                }
+            }
+        }
+    }
+
+    // Release events are not received once the window lost the focus
+    pub fn release_all(&mut self) {
+        self.just_released_keys.append(&mut self.held_keys);
+    }
+
+    pub(crate) fn clear_edges(&mut self) {
+        self.just_pressed_keys.clear();
+        self.just_released_keys.clear();
+    }
+}
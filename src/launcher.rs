@@ -24,14 +24,19 @@ SOFTWARE.
 
 use log::info;
 
-use crate::{scenario::Scenario, window::init_event_loop};
+use crate::scenario::Scenario;
+use crate::window::{init_event_loop_with_config, AppConfig};
 
 const GLOBAL_LOG_FILTER: log::LevelFilter = log::LevelFilter::Debug;
 
 pub fn launch_scenario<S: Scenario + 'static>() {
+    launch_scenario_with_config::<S>(AppConfig::default());
+}
+
+pub fn launch_scenario_with_config<S: Scenario + 'static>(config: AppConfig) {
     init_log();
     info!("Init app");
-    init_event_loop::<S>();
+    init_event_loop_with_config::<S>(config);
 }
 
 fn init_log() {
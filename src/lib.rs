@@ -23,8 +23,26 @@ SOFTWARE.
 */
 
 pub mod cameras;
+pub mod canvas;
 pub mod draw_context;
+pub mod frustum;
+#[cfg(feature = "egui")]
+pub mod gui;
+pub mod input;
 pub mod launcher;
+pub mod lights;
+pub mod loaders;
+pub mod post_process;
 pub mod primitives;
 pub mod scenario;
+pub mod scene;
+#[cfg(feature = "serde")]
+pub mod scene_format;
+#[cfg(all(feature = "hot-reload", not(target_arch = "wasm32")))]
+pub mod shader_watcher;
+#[cfg(feature = "text")]
+pub mod text;
+pub mod textures;
+pub mod timing;
+pub mod uniforms;
 pub mod window;
@@ -0,0 +1,64 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+pub const LAMBERT_SHADER: &str = concat!(
+    include_str!("shaders/scene_globals.wgsl"),
+    include_str!("shaders/lambert.wgsl")
+);
+
+pub struct Light {
+    pub direction: [f32; 3],
+    pub color: [f32; 3],
+    pub ambient: f32,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Light {
+            direction: [0.5, -1.0, 1.0],
+            color: [1.0, 1.0, 1.0],
+            ambient: 0.1,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightUniform {
+    direction: [f32; 3],
+    ambient: f32,
+    color: [f32; 3],
+    _padding: f32,
+}
+
+impl From<&Light> for LightUniform {
+    fn from(light: &Light) -> Self {
+        LightUniform {
+            direction: light.direction,
+            ambient: light.ambient,
+            color: light.color,
+            _padding: 0.,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::draw_context::{DrawContext, Drawable, DrawableOptions, IndexData, NormalVertex};
+use crate::primitives::{Bounds, Object3D};
+
+const DEFAULT_COLOR: [f32; 3] = [1., 1., 1.];
+
+pub fn load_gltf(
+    context: &DrawContext,
+    bytes: &[u8],
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+) -> anyhow::Result<Vec<Object3D>> {
+    let (document, buffers, _images) =
+        ::gltf::import_slice(bytes).context("Failed to parse glTF data")?;
+    create_objects(context, &document, &buffers, vertex_state, fragment_state)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_gltf_file(
+    context: &DrawContext,
+    path: impl AsRef<std::path::Path>,
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+) -> anyhow::Result<Vec<Object3D>> {
+    let path = path.as_ref();
+    let (document, buffers, _images) = ::gltf::import(path)
+        .with_context(|| format!("Failed to load glTF file {}", path.display()))?;
+    create_objects(context, &document, &buffers, vertex_state, fragment_state)
+}
+
+fn create_objects(
+    context: &DrawContext,
+    document: &::gltf::Document,
+    buffers: &[::gltf::buffer::Data],
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+) -> anyhow::Result<Vec<Object3D>> {
+    let mut objects = Vec::new();
+    for mesh in document.meshes() {
+        for primitive in mesh.primitives() {
+            if primitive.mode() != ::gltf::mesh::Mode::Triangles {
+                bail!("Unsupported glTF primitive mode {:?}", primitive.mode());
+            }
+            let reader = primitive
+                .reader(|buffer| buffers.get(buffer.index()).map(|data| data.0.as_slice()));
+            let positions = reader
+                .read_positions()
+                .ok_or_else(|| anyhow!("glTF primitive has no position attribute"))?;
+            let normals: Vec<[f32; 3]> = reader
+                .read_normals()
+                .map(|normals| normals.collect())
+                .unwrap_or_default();
+            // glTF is right-handed, Z is flipped to match the left-handed camera convention, which
+            // also turns its counter-clockwise front faces clockwise
+            let options = DrawableOptions {
+                front_face: wgpu::FrontFace::Cw,
+                ..Default::default()
+            };
+            let vertices: Vec<NormalVertex> = positions
+                .enumerate()
+                .map(|(i, [x, y, z])| {
+                    let [nx, ny, nz] = normals.get(i).copied().unwrap_or_default();
+                    NormalVertex {
+                        position: [x, y, -z],
+                        color: DEFAULT_COLOR,
+                        normal: [nx, ny, -nz],
+                    }
+                })
+                .collect();
+            let drawable = match reader.read_indices() {
+                Some(indices) => {
+                    let indices: Vec<u32> = indices.into_u32().collect();
+                    if !indices.len().is_multiple_of(3) {
+                        bail!("glTF index count is not a multiple of 3");
+                    }
+                    if indices.iter().any(|&i| i as usize >= vertices.len()) {
+                        bail!("glTF index out of the vertex range");
+                    }
+                    let triangles = indices
+                        .chunks_exact(3)
+                        .map(|t| [t[0], t[1], t[2]])
+                        .collect();
+                    let index_data = IndexData::from_triangles(vertices.len(), triangles);
+                    Drawable::init_indexed_with_options(
+                        context,
+                        &vertices,
+                        &index_data,
+                        vertex_state.clone(),
+                        fragment_state.clone(),
+                        options,
+                    )
+                }
+                None => Drawable::init_direct_with_options(
+                    context,
+                    &vertices,
+                    vertex_state.clone(),
+                    fragment_state.clone(),
+                    options,
+                ),
+            };
+            let mut object = Object3D::from_drawable(drawable);
+            if let Some(bounds) =
+                Bounds::from_positions(vertices.iter().map(|vertex| vertex.position))
+            {
+                object.set_local_bounds(bounds);
+            }
+            objects.push(object);
+        }
+    }
+    Ok(objects)
+}
@@ -0,0 +1,202 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::draw_context::{Dimensions, DrawContext};
+
+const POST_PROCESS_PRELUDE_SHADER: &str = include_str!("shaders/post_process_prelude.wgsl");
+
+// Scene rendered in a texture with the render size and the surface format, then sampled by the
+// post-process, see DrawContext::set_render_scale
+pub(crate) struct PostProcessSource {
+    pub(crate) view: wgpu::TextureView,
+    pub(crate) bind_group: wgpu::BindGroup,
+}
+
+impl PostProcessSource {
+    pub(crate) fn new(context: &DrawContext) -> Self {
+        let Dimensions { width, height } = context.render_size();
+        let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Post-process Source Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: context.surface_format(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = context.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post-process Source Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post-process Source bind group"),
+                layout: &context.texture_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+        PostProcessSource { view, bind_group }
+    }
+}
+
+// Fullscreen pass applied to the rendered scene, see DrawContext::render_scene_with_post.
+// The fragment code defines `fn post_process(uv: vec2<f32>) -> vec4<f32>`, with access to
+// `source_texture`, `source_sampler` and the `params` uniform.
+pub struct PostProcess {
+    pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
+}
+
+impl PostProcess {
+    pub const BIND_GROUP_INDEX_SOURCE: u32 = 0;
+    pub const BIND_GROUP_INDEX_PARAMS: u32 = 1;
+
+    pub fn new(context: &DrawContext, fragment_wgsl: &str) -> anyhow::Result<Self> {
+        let shader_source = format!("{POST_PROCESS_PRELUDE_SHADER}{fragment_wgsl}");
+        let shader_module = context.create_shader_module("Post-process Shader", &shader_source)?;
+        let params_buffer = context.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Post-process Params Buffer"),
+            contents: bytemuck::bytes_of(&[0f32; 4]),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+        });
+        let params_bind_group_layout =
+            context.get_bind_group_layout(&[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }]);
+        let params_bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post-process Params bind group"),
+                layout: &params_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                }],
+            });
+        let pipeline_layout =
+            context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Post-process Pipeline Layout"),
+                    bind_group_layouts: &[
+                        &*context.texture_bind_group_layout,
+                        &*params_bind_group_layout,
+                    ],
+                    push_constant_ranges: &[],
+                });
+        let pipeline = context.with_validation_scope("Failed to create post-process", || {
+            context
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    cache: None,
+                    label: Some("Post-process Pipeline"),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader_module,
+                        entry_point: Some("vtx_main"),
+                        buffers: &[],
+                        compilation_options: Default::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader_module,
+                        entry_point: Some("frg_main"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: context.surface_format(),
+                            blend: Some(wgpu::BlendState::REPLACE),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: Default::default(),
+                    }),
+                    primitive: Default::default(),
+                    depth_stencil: None,
+                    multisample: Default::default(),
+                    multiview: None,
+                })
+        })?;
+        Ok(PostProcess {
+            pipeline,
+            params_buffer,
+            params_bind_group,
+        })
+    }
+
+    pub fn set_params(&self, context: &DrawContext, params: [f32; 4]) {
+        context
+            .queue
+            .write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    pub(crate) fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &PostProcessSource,
+        target_view: &wgpu::TextureView,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Post-process pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(Self::BIND_GROUP_INDEX_SOURCE, &source.bind_group, &[]);
+        render_pass.set_bind_group(Self::BIND_GROUP_INDEX_PARAMS, &self.params_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
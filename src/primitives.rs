@@ -22,17 +22,90 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+pub mod color;
+pub mod cone;
 pub mod cube;
+pub mod cylinder;
+pub mod gizmo;
+pub mod lines;
+pub mod quad;
+pub mod skybox;
+pub mod sphere;
 pub mod triangle;
 
 use crate::draw_context::{DrawContext, Drawable};
-use cgmath::Matrix4;
-use cgmath::SquareMatrix;
+use crate::scenario::UpdateInterval;
+use crate::scene::SceneUpdate;
+use cgmath::{EuclideanSpace, InnerSpace, Matrix, MetricSpace, SquareMatrix, Transform};
+use cgmath::{Matrix3, Matrix4, Point3, Quaternion, Vector3};
+use log::error;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Bounds {
+    // Returns None if there is no position
+    pub fn from_positions(positions: impl IntoIterator<Item = [f32; 3]>) -> Option<Self> {
+        let mut positions = positions.into_iter().map(Point3::from);
+        let first = positions.next()?;
+        Some(positions.fold(
+            Bounds {
+                min: first,
+                max: first,
+            },
+            |bounds, position| Bounds {
+                min: Point3::new(
+                    bounds.min.x.min(position.x),
+                    bounds.min.y.min(position.y),
+                    bounds.min.z.min(position.z),
+                ),
+                max: Point3::new(
+                    bounds.max.x.max(position.x),
+                    bounds.max.y.max(position.y),
+                    bounds.max.z.max(position.z),
+                ),
+            },
+        ))
+    }
+    pub fn center(&self) -> Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+    // Radius of the bounding sphere around the center
+    pub fn radius(&self) -> f32 {
+        self.center().distance(self.max)
+    }
+    pub fn corners(&self) -> [Point3<f32>; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Point3::new(min.x, min.y, min.z),
+            Point3::new(max.x, min.y, min.z),
+            Point3::new(min.x, max.y, min.z),
+            Point3::new(max.x, max.y, min.z),
+            Point3::new(min.x, min.y, max.z),
+            Point3::new(max.x, min.y, max.z),
+            Point3::new(min.x, max.y, max.z),
+            Point3::new(max.x, max.y, max.z),
+        ]
+    }
+    // Axis aligned box enclosing the transformed corners
+    pub fn transform(&self, transform: &Matrix4<f32>) -> Self {
+        Bounds::from_positions(
+            self.corners()
+                .map(|corner| transform.transform_point(corner).into()),
+        )
+        .expect("Corners are never empty")
+    }
+}
 
 pub struct Object3D {
     drawable: Drawable,
     transform: Matrix4<f32>,
     opacity: f32,
+    billboard: bool,
+    local_bounds: Option<Bounds>,
 }
 
 impl Object3D {
@@ -41,6 +114,8 @@ impl Object3D {
             drawable,
             transform: Matrix4::<f32>::identity(),
             opacity: 1.0,
+            billboard: false,
+            local_bounds: None,
         }
     }
     pub fn set_transform(&mut self, context: &DrawContext, transform: Matrix4<f32>) {
@@ -54,13 +129,54 @@ impl Object3D {
         self.transform = self.transform * transform; // TODO Shouldn't it be the opposite? But in that case that does not work
         self.drawable.set_transform(context, self.transform);
     }
-    pub fn set_opacity(&mut self, value: f32) {
+    // Drives both the blend constant and the shader opacity, so it works with
+    // BlendMode::ConstantOpacity and with BlendMode::AlphaBlend shaders reading the opacity
+    pub fn set_opacity(&mut self, context: &DrawContext, value: f32) {
         self.opacity = value.clamp(0., 1.);
         self.drawable.set_blend_color_opacity(self.opacity as f64);
+        self.drawable.set_opacity(context, self.opacity);
     }
     pub fn get_opacity(&self) -> f32 {
         self.opacity
     }
+    pub fn set_local_bounds(&mut self, bounds: Bounds) {
+        self.local_bounds = Some(bounds);
+    }
+    pub fn local_bounds(&self) -> Option<Bounds> {
+        self.local_bounds
+    }
+    pub fn world_bounds(&self) -> Option<Bounds> {
+        self.local_bounds
+            .map(|bounds| bounds.transform(&self.transform))
+    }
+    pub fn set_billboard(&mut self, billboard: bool) {
+        self.billboard = billboard;
+    }
+    pub fn is_billboard(&self) -> bool {
+        self.billboard
+    }
+    // Keeps position and scale, but replaces rotation so the local -Z axis points toward the camera
+    pub fn face_camera(&mut self, context: &DrawContext, camera_view: &Matrix4<f32>) {
+        if !self.billboard {
+            return;
+        }
+        let translation = self.transform.w.truncate();
+        let scale = Matrix4::from_nonuniform_scale(
+            self.transform.x.truncate().magnitude(),
+            self.transform.y.truncate().magnitude(),
+            self.transform.z.truncate().magnitude(),
+        );
+        let view_rotation = Matrix3::from_cols(
+            camera_view.x.truncate(),
+            camera_view.y.truncate(),
+            camera_view.z.truncate(),
+        );
+        let rotation = Matrix4::from(view_rotation.transpose());
+        self.set_transform(
+            context,
+            Matrix4::from_translation(translation) * rotation * scale,
+        );
+    }
 }
 
 impl AsRef<Drawable> for Object3D {
@@ -68,3 +184,164 @@ impl AsRef<Drawable> for Object3D {
         &self.drawable
     }
 }
+
+// E.g. to change the shaders with Drawable::replace_shaders
+impl AsMut<Drawable> for Object3D {
+    fn as_mut(&mut self) -> &mut Drawable {
+        &mut self.drawable
+    }
+}
+
+// The transform already written in the drawable buffer is kept
+impl From<Object3D> for Drawable {
+    fn from(object: Object3D) -> Self {
+        object.drawable
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct InstanceData {
+    pub model: [[f32; 4]; 4],
+}
+
+impl InstanceData {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] =
+        wgpu::vertex_attr_array![5 => Float32x4, 6 => Float32x4, 7 => Float32x4, 8 => Float32x4];
+
+    pub fn vertex_buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Object3DInstance {
+    pub position: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: f32,
+}
+
+impl Object3DInstance {
+    pub fn set_position(&mut self, position: Vector3<f32>) {
+        self.position = position;
+    }
+    pub fn set_rotation(&mut self, rotation: Quaternion<f32>) {
+        self.rotation = rotation;
+    }
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+    pub fn get_transform(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.position)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_scale(self.scale)
+    }
+    fn to_instance_data(self) -> InstanceData {
+        InstanceData {
+            model: self.get_transform().into(),
+        }
+    }
+}
+
+impl Default for Object3DInstance {
+    fn default() -> Self {
+        Object3DInstance {
+            position: Vector3::new(0., 0., 0.),
+            rotation: Quaternion::new(1., 0., 0., 0.),
+            scale: 1.,
+        }
+    }
+}
+
+impl From<Vector3<f32>> for Object3DInstance {
+    fn from(position: Vector3<f32>) -> Self {
+        Object3DInstance {
+            position,
+            ..Default::default()
+        }
+    }
+}
+
+pub type InstanceAnimation = Box<dyn FnMut(&UpdateInterval, usize, &mut Object3DInstance)>;
+
+pub struct Object3DInstanceGroup {
+    object: Object3D,
+    instances: Vec<Object3DInstance>,
+    animation: Option<InstanceAnimation>,
+}
+
+impl Object3DInstanceGroup {
+    pub fn new(
+        context: &DrawContext,
+        drawable: Drawable,
+        instances: Vec<Object3DInstance>,
+    ) -> Self {
+        let mut group = Object3DInstanceGroup {
+            object: Object3D::from_drawable(drawable),
+            instances,
+            animation: None,
+        };
+        group.write_instances(context);
+        group
+    }
+    pub fn get_object(&self) -> &Object3D {
+        &self.object
+    }
+    pub fn get_object_mut(&mut self) -> &mut Object3D {
+        &mut self.object
+    }
+    pub fn get_instances(&self) -> &[Object3DInstance] {
+        &self.instances
+    }
+    pub fn update_instances<F>(&mut self, context: &DrawContext, mut update: F)
+    where
+        F: FnMut(usize, &mut Object3DInstance),
+    {
+        for (index, instance) in self.instances.iter_mut().enumerate() {
+            update(index, instance);
+        }
+        self.write_instances(context);
+    }
+    // Called with the index of each instance when the group is updated by a Scene3D
+    pub fn set_animation(
+        &mut self,
+        animation: impl FnMut(&UpdateInterval, usize, &mut Object3DInstance) + 'static,
+    ) {
+        self.animation = Some(Box::new(animation));
+    }
+    fn write_instances(&mut self, context: &DrawContext) {
+        let instance_data: Vec<InstanceData> = self
+            .instances
+            .iter()
+            .map(|instance| instance.to_instance_data())
+            .collect();
+        self.object
+            .drawable
+            .set_instance_data(context, &instance_data);
+    }
+}
+
+impl AsRef<Drawable> for Object3DInstanceGroup {
+    fn as_ref(&self) -> &Drawable {
+        &self.object.drawable
+    }
+}
+
+impl SceneUpdate for Object3DInstanceGroup {
+    fn scene_update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        if let Err(err) = self.object.drawable.refresh_pipelines(context) {
+            error!("Could not refresh the instance group pipelines: {err:#}");
+        }
+        let Some(mut animation) = self.animation.take() else {
+            return;
+        };
+        self.update_instances(context, |index, instance| {
+            animation(update_interval, index, instance)
+        });
+        self.animation = Some(animation);
+    }
+}
@@ -0,0 +1,137 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+// Vertex colors are linear RGB: shaders output them as is, and the sRGB surface formats encode
+// them when writing. Colors picked in an image editor or given as hex codes are sRGB encoded and
+// must be converted, e.g. with Color::from_srgb_u8, or they look washed out.
+// The constants below only use 0 and 1 components, which are the same in both spaces.
+
+pub const WHITE: [f32; 3] = [1., 1., 1.];
+pub const BLACK: [f32; 3] = [0., 0., 0.];
+pub const RED: [f32; 3] = [1., 0., 0.];
+pub const GREEN: [f32; 3] = [0., 1., 0.];
+pub const BLUE: [f32; 3] = [0., 0., 1.];
+pub const YELLOW: [f32; 3] = [1., 1., 0.];
+pub const CYAN: [f32; 3] = [0., 1., 1.];
+pub const MAGENTA: [f32; 3] = [1., 0., 1.];
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+// Hue in degrees, saturation and value from 0 to 1. HSV and HSL are defined on sRGB values, the
+// result is converted to linear RGB for the vertex attributes.
+pub fn hsv(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let chroma = value * saturation;
+    Color::from_srgb(hue_to_rgb(hue, chroma, value - chroma)).0
+}
+
+// Hue in degrees, saturation and lightness from 0 to 1
+pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+    let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+    Color::from_srgb(hue_to_rgb(hue, chroma, lightness - chroma / 2.)).0
+}
+
+fn hue_to_rgb(hue: f32, chroma: f32, min: f32) -> [f32; 3] {
+    let sector = hue.rem_euclid(360.) / 60.;
+    let x = chroma * (1. - (sector % 2. - 1.).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    [r + min, g + min, b + min]
+}
+
+// Linear RGB color, as expected by the vertex attributes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color(pub [f32; 3]);
+
+impl Color {
+    pub fn from_srgb(srgb: [f32; 3]) -> Self {
+        Color(srgb.map(srgb_to_linear))
+    }
+
+    pub fn from_srgb_u8(r: u8, g: u8, b: u8) -> Self {
+        Self::from_srgb([r, g, b].map(|component| component as f32 / 255.))
+    }
+
+    pub fn to_srgb(self) -> [f32; 3] {
+        self.0.map(linear_to_srgb)
+    }
+}
+
+impl From<Color> for [f32; 3] {
+    fn from(color: Color) -> Self {
+        color.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn assert_color_eq(actual: [f32; 3], expected: [f32; 3]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < EPSILON, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn hsv_primary_hues() {
+        assert_color_eq(hsv(0., 1., 1.), RED);
+        assert_color_eq(hsv(120., 1., 1.), GREEN);
+        assert_color_eq(hsv(240., 1., 1.), BLUE);
+    }
+
+    #[test]
+    fn hsl_mid_grey_is_linear() {
+        let grey = srgb_to_linear(0.5);
+        assert_color_eq(hsl(0., 0., 0.5), [grey; 3]);
+    }
+
+    #[test]
+    fn hue_wraps_at_360() {
+        assert_color_eq(hsv(360., 1., 1.), RED);
+        assert_color_eq(hsv(480., 1., 1.), hsv(120., 1., 1.));
+        assert_color_eq(hsl(-120., 1., 0.5), hsl(240., 1., 0.5));
+    }
+}
@@ -0,0 +1,133 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::f32::consts::PI;
+
+use crate::draw_context::Drawable;
+use crate::draw_context::{DrawContext, IndexData, NormalVertex};
+use crate::primitives::{Bounds, Object3D};
+
+const MIN_SEGMENTS: u16 = 3;
+
+pub struct ConeOptions {
+    pub with_base: bool,
+    pub color: [f32; 3],
+}
+
+impl Default for ConeOptions {
+    fn default() -> Self {
+        ConeOptions {
+            with_base: true,
+            color: [1., 1., 1.],
+        }
+    }
+}
+
+// Cone base is centered on the origin, the apex is at (0, height, 0), so that it can be put at the
+// end of a cylinder to build an arrow
+pub fn create_cone(
+    context: &DrawContext,
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+    segments: u16,
+    height: f32,
+    radius: f32,
+    options: ConeOptions,
+) -> Object3D {
+    let segments = segments.max(MIN_SEGMENTS);
+    let color = options.color;
+    let ring = |i: f32| {
+        let angle = 2. * PI * i / segments as f32;
+        (angle.cos(), angle.sin())
+    };
+    // Side normals are tilted up by the slope of the cone
+    let side_normal = |(cos, sin): (f32, f32)| {
+        let length = (height * height + radius * radius).sqrt();
+        [
+            cos * height / length,
+            radius / length,
+            sin * height / length,
+        ]
+    };
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for i in 0..=segments {
+        let (cos, sin) = ring(i as f32);
+        vertices.push(NormalVertex {
+            position: [radius * cos, 0., radius * sin],
+            color,
+            normal: side_normal((cos, sin)),
+        });
+        // One apex vertex per segment, with the normal of the middle of the segment
+        vertices.push(NormalVertex {
+            position: [0., height, 0.],
+            color,
+            normal: side_normal(ring(i as f32 + 0.5)),
+        });
+    }
+    for i in 0..segments as u32 {
+        let base = 2 * i;
+        let apex = base + 1;
+        let next_base = base + 2;
+        indices.push([base, next_base, apex]);
+    }
+
+    if options.with_base {
+        let center = vertices.len() as u32;
+        let normal = [0., -1., 0.];
+        vertices.push(NormalVertex {
+            position: [0., 0., 0.],
+            color,
+            normal,
+        });
+        for i in 0..=segments {
+            let (cos, sin) = ring(i as f32);
+            vertices.push(NormalVertex {
+                position: [radius * cos, 0., radius * sin],
+                color,
+                normal,
+            });
+        }
+        for i in 0..segments as u32 {
+            let current = center + 1 + i;
+            indices.push([center, current + 1, current]);
+        }
+    }
+
+    let index_data = IndexData::from_triangles(vertices.len(), indices);
+    let drawable = Drawable::init_indexed_with_options(
+        context,
+        &vertices,
+        &index_data,
+        vertex_state,
+        fragment_state,
+        Default::default(),
+    );
+    let mut cone = Object3D::from_drawable(drawable);
+    if let Some(bounds) = Bounds::from_positions(vertices.iter().map(|vertex| vertex.position)) {
+        cone.set_local_bounds(bounds);
+    }
+    cone
+}
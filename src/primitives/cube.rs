@@ -23,50 +23,42 @@ SOFTWARE.
 */
 
 use crate::draw_context::Drawable;
-use crate::draw_context::{DrawContext, Vertex};
-use crate::primitives::Object3D;
-
-const COLOR_WHITE: [f32; 3] = [1., 1., 1.];
-const COLOR_BLACK: [f32; 3] = [0., 0., 0.];
-const COLOR_RED: [f32; 3] = [1., 0., 0.];
-const COLOR_GREEN: [f32; 3] = [0., 1., 0.];
-const COLOR_BLUE: [f32; 3] = [0., 0., 1.];
-const COLOR_YELLOW: [f32; 3] = [1., 1., 0.];
-const COLOR_CYAN: [f32; 3] = [0., 1., 1.];
-const COLOR_MAGENTA: [f32; 3] = [1., 0., 1.];
+use crate::draw_context::{DrawContext, NormalVertex, Vertex};
+use crate::primitives::color::{BLACK, BLUE, CYAN, GREEN, MAGENTA, RED, WHITE, YELLOW};
+use crate::primitives::{Bounds, Object3D, Object3DInstance, Object3DInstanceGroup};
 
 const CUBE_VERTICES: &[Vertex] = &[
     Vertex {
         position: [-0.5, 0.5, -0.5],
-        color: COLOR_MAGENTA,
+        color: MAGENTA,
     },
     Vertex {
         position: [0.5, 0.5, -0.5],
-        color: COLOR_WHITE,
+        color: WHITE,
     },
     Vertex {
         position: [0.5, -0.5, -0.5],
-        color: COLOR_RED,
+        color: RED,
     },
     Vertex {
         position: [-0.5, -0.5, -0.5],
-        color: COLOR_BLACK,
+        color: BLACK,
     },
     Vertex {
         position: [-0.5, 0.5, 0.5],
-        color: COLOR_BLUE,
+        color: BLUE,
     },
     Vertex {
         position: [0.5, 0.5, 0.5],
-        color: COLOR_YELLOW,
+        color: YELLOW,
     },
     Vertex {
         position: [0.5, -0.5, 0.5],
-        color: COLOR_CYAN,
+        color: CYAN,
     },
     Vertex {
         position: [-0.5, -0.5, 0.5],
-        color: COLOR_GREEN,
+        color: GREEN,
     },
 ];
 
@@ -103,5 +95,63 @@ pub fn create_cube(
         vertex_state,
         fragment_state,
     );
-    Object3D::from_drawable(drawable)
+    let mut cube = Object3D::from_drawable(drawable);
+    cube.set_local_bounds(cube_bounds());
+    cube
+}
+
+fn cube_bounds() -> Bounds {
+    Bounds::from_positions(CUBE_VERTICES.iter().map(|vertex| vertex.position))
+        .expect("Cube has vertices")
+}
+
+// Same order as the faces in CUBE_INDICES
+const CUBE_FACE_NORMALS: &[[f32; 3]] = &[
+    [0., 0., -1.],
+    [0., 0., 1.],
+    [0., 1., 0.],
+    [0., -1., 0.],
+    [-1., 0., 0.],
+    [1., 0., 0.],
+];
+
+pub fn create_cube_with_normals(
+    context: &DrawContext,
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+) -> Object3D {
+    let vertices: Vec<NormalVertex> = CUBE_INDICES
+        .chunks_exact(2)
+        .zip(CUBE_FACE_NORMALS)
+        .flat_map(|(face, normal)| {
+            face.iter().flatten().map(|&index| {
+                let vertex = CUBE_VERTICES[index as usize];
+                NormalVertex {
+                    position: vertex.position,
+                    color: vertex.color,
+                    normal: *normal,
+                }
+            })
+        })
+        .collect();
+    let drawable = Drawable::init_direct(context, &vertices, vertex_state, fragment_state);
+    let mut cube = Object3D::from_drawable(drawable);
+    cube.set_local_bounds(cube_bounds());
+    cube
+}
+
+pub fn create_cube_instances(
+    context: &DrawContext,
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+    instances: &[Object3DInstance],
+) -> Object3DInstanceGroup {
+    let drawable = Drawable::init_indexed(
+        context,
+        CUBE_VERTICES,
+        CUBE_INDICES,
+        vertex_state,
+        fragment_state,
+    );
+    Object3DInstanceGroup::new(context, drawable, instances.to_vec())
 }
@@ -0,0 +1,133 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::f32::consts::PI;
+
+use crate::draw_context::Drawable;
+use crate::draw_context::{DrawContext, IndexData, NormalVertex};
+use crate::primitives::{Bounds, Object3D};
+
+const MIN_SEGMENTS: u16 = 3;
+
+pub struct CylinderOptions {
+    pub with_caps: bool,
+    pub color: [f32; 3],
+}
+
+impl Default for CylinderOptions {
+    fn default() -> Self {
+        CylinderOptions {
+            with_caps: true,
+            color: [1., 1., 1.],
+        }
+    }
+}
+
+// Cylinder is centered on the origin, along the Y axis
+pub fn create_cylinder(
+    context: &DrawContext,
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+    segments: u16,
+    height: f32,
+    radius: f32,
+    options: CylinderOptions,
+) -> Object3D {
+    let segments = segments.max(MIN_SEGMENTS);
+    let half_height = height / 2.;
+    let color = options.color;
+    let ring = |i: u16| {
+        let angle = 2. * PI * i as f32 / segments as f32;
+        (angle.cos(), angle.sin())
+    };
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for i in 0..=segments {
+        let (cos, sin) = ring(i);
+        let normal = [cos, 0., sin];
+        vertices.push(NormalVertex {
+            position: [radius * cos, -half_height, radius * sin],
+            color,
+            normal,
+        });
+        vertices.push(NormalVertex {
+            position: [radius * cos, half_height, radius * sin],
+            color,
+            normal,
+        });
+    }
+    for i in 0..segments as u32 {
+        let bottom = 2 * i;
+        let top = bottom + 1;
+        let next_bottom = bottom + 2;
+        let next_top = bottom + 3;
+        indices.push([bottom, next_bottom, top]);
+        indices.push([top, next_bottom, next_top]);
+    }
+
+    if options.with_caps {
+        for (y, normal_y) in [(half_height, 1.), (-half_height, -1.)] {
+            let center = vertices.len() as u32;
+            let normal = [0., normal_y, 0.];
+            vertices.push(NormalVertex {
+                position: [0., y, 0.],
+                color,
+                normal,
+            });
+            for i in 0..=segments {
+                let (cos, sin) = ring(i);
+                vertices.push(NormalVertex {
+                    position: [radius * cos, y, radius * sin],
+                    color,
+                    normal,
+                });
+            }
+            for i in 0..segments as u32 {
+                let current = center + 1 + i;
+                let next = current + 1;
+                if normal_y > 0. {
+                    indices.push([center, current, next]);
+                } else {
+                    indices.push([center, next, current]);
+                }
+            }
+        }
+    }
+
+    let index_data = IndexData::from_triangles(vertices.len(), indices);
+    let drawable = Drawable::init_indexed_with_options(
+        context,
+        &vertices,
+        &index_data,
+        vertex_state,
+        fragment_state,
+        Default::default(),
+    );
+    let mut cylinder = Object3D::from_drawable(drawable);
+    if let Some(bounds) = Bounds::from_positions(vertices.iter().map(|vertex| vertex.position)) {
+        cylinder.set_local_bounds(bounds);
+    }
+    cylinder
+}
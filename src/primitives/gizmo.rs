@@ -0,0 +1,114 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{EuclideanSpace, Matrix4, Point3, SquareMatrix, Vector3};
+
+use crate::draw_context::{DrawContext, Drawable, DrawableOptions, Vertex};
+
+const AXES: [([f32; 3], [f32; 3]); 3] = [
+    ([1., 0., 0.], [1., 0., 0.]),
+    ([0., 1., 0.], [0., 1., 0.]),
+    ([0., 0., 1.], [0., 0., 1.]),
+];
+
+// X, Y and Z axes drawn as red, green and blue lines of the given length, starting at the origin
+// of the gizmo. With always_visible, the axes are not hidden by the scene and the gizmo should be
+// rendered last.
+pub struct Gizmo {
+    drawable: Drawable,
+    size: f32,
+}
+
+impl Gizmo {
+    pub fn new(
+        context: &DrawContext,
+        vertex_state: wgpu::VertexState,
+        fragment_state: wgpu::FragmentState,
+        size: f32,
+        always_visible: bool,
+    ) -> Self {
+        let vertices: Vec<_> = AXES
+            .iter()
+            .flat_map(|&(axis, color)| {
+                [
+                    Vertex {
+                        position: [0.; 3],
+                        color,
+                    },
+                    Vertex {
+                        position: axis.map(|coord| coord * size),
+                        color,
+                    },
+                ]
+            })
+            .collect();
+        let drawable = Drawable::init_direct_with_options(
+            context,
+            &vertices,
+            vertex_state,
+            fragment_state,
+            DrawableOptions {
+                topology: wgpu::PrimitiveTopology::LineList,
+                always_visible,
+                ..Default::default()
+            },
+        );
+        Gizmo { drawable, size }
+    }
+
+    pub fn get_size(&self) -> f32 {
+        self.size
+    }
+
+    pub fn set_position(&mut self, context: &DrawContext, position: Point3<f32>) {
+        self.drawable
+            .set_transform(context, Matrix4::from_translation(position.to_vec()));
+    }
+
+    // Keeps the gizmo at a fixed place in front of the camera, with the world orientation. The
+    // offset is in view coordinates, x to the right, y up and z forward, so that e.g.
+    // (-0.8, -0.5, 2.) puts it in the bottom left corner. To call after each camera update.
+    pub fn follow_camera(
+        &mut self,
+        context: &DrawContext,
+        camera_view: &Matrix4<f32>,
+        view_offset: Vector3<f32>,
+    ) {
+        let Some(camera_transform) = camera_view.invert() else {
+            return;
+        };
+        let position = camera_transform * view_offset.extend(1.);
+        self.set_position(context, Point3::from_homogeneous(position));
+    }
+
+    pub fn render<'drawable>(&'drawable self, render_pass: &mut wgpu::RenderPass<'drawable>) {
+        self.drawable.render(render_pass);
+    }
+}
+
+impl AsRef<Drawable> for Gizmo {
+    fn as_ref(&self) -> &Drawable {
+        &self.drawable
+    }
+}
@@ -0,0 +1,132 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::draw_context::{DrawContext, Drawable, DrawableOptions, Vertex};
+use crate::primitives::Bounds;
+
+const INITIAL_LINE_CAPACITY: usize = 64;
+
+// Pairs of corner indices, as ordered by Bounds::corners
+const AABB_EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (2, 3),
+    (4, 5),
+    (6, 7),
+    (0, 2),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+// Accumulates debug line segments rendered in a single draw call, with the Vertex layout.
+// Lines are kept until clear() is called, and uploaded to the GPU with update().
+pub struct LineBatch {
+    drawable: Drawable,
+    vertices: Vec<Vertex>,
+    dirty: bool,
+}
+
+impl LineBatch {
+    pub fn new(
+        context: &DrawContext,
+        vertex_state: wgpu::VertexState,
+        fragment_state: wgpu::FragmentState,
+    ) -> Self {
+        let mut drawable = Drawable::init_direct_with_options(
+            context,
+            &[Vertex {
+                position: [0.; 3],
+                color: [0.; 3],
+            }; 2 * INITIAL_LINE_CAPACITY],
+            vertex_state,
+            fragment_state,
+            DrawableOptions {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+        );
+        drawable.set_vertices::<Vertex>(context, &[]);
+        LineBatch {
+            drawable,
+            vertices: Vec::with_capacity(2 * INITIAL_LINE_CAPACITY),
+            dirty: false,
+        }
+    }
+
+    pub fn add_line(&mut self, start: [f32; 3], end: [f32; 3], color: [f32; 3]) {
+        self.vertices.push(Vertex {
+            position: start,
+            color,
+        });
+        self.vertices.push(Vertex {
+            position: end,
+            color,
+        });
+        self.dirty = true;
+    }
+
+    pub fn add_aabb(&mut self, bounds: &Bounds, color: [f32; 3]) {
+        let corners = bounds.corners();
+        for (start, end) in AABB_EDGES {
+            self.add_line(corners[start].into(), corners[end].into(), color);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.dirty = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.vertices.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    pub fn set_transform(&mut self, context: &DrawContext, transform: impl AsRef<[[f32; 4]; 4]>) {
+        self.drawable.set_transform(context, transform);
+    }
+
+    // Uploads the lines if they changed since the last call
+    pub fn update(&mut self, context: &DrawContext) {
+        if !self.dirty {
+            return;
+        }
+        self.drawable.set_vertices(context, &self.vertices);
+        self.dirty = false;
+    }
+
+    pub fn render<'drawable>(&'drawable self, render_pass: &mut wgpu::RenderPass<'drawable>) {
+        if self.is_empty() {
+            return;
+        }
+        self.drawable.render(render_pass);
+    }
+}
@@ -0,0 +1,72 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::draw_context::Drawable;
+use crate::draw_context::{DrawContext, TexturedVertex};
+use crate::primitives::{Bounds, Object3D};
+
+const QUAD_INDICES: &[[u16; 3]] = &[[0, 2, 1], [0, 3, 2]];
+
+// Quad lies in the XY plane, its front face looking toward -Z
+pub fn create_quad(
+    context: &DrawContext,
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+    width: f32,
+    height: f32,
+    billboard: bool,
+) -> Object3D {
+    let (half_width, half_height) = (width / 2., height / 2.);
+    let vertices = [
+        TexturedVertex {
+            position: [-half_width, half_height, 0.],
+            uv: [0., 0.],
+        },
+        TexturedVertex {
+            position: [half_width, half_height, 0.],
+            uv: [1., 0.],
+        },
+        TexturedVertex {
+            position: [half_width, -half_height, 0.],
+            uv: [1., 1.],
+        },
+        TexturedVertex {
+            position: [-half_width, -half_height, 0.],
+            uv: [0., 1.],
+        },
+    ];
+    let drawable = Drawable::init_indexed(
+        context,
+        &vertices,
+        QUAD_INDICES,
+        vertex_state,
+        fragment_state,
+    );
+    let mut quad = Object3D::from_drawable(drawable);
+    if let Some(bounds) = Bounds::from_positions(vertices.map(|vertex| vertex.position)) {
+        quad.set_local_bounds(bounds);
+    }
+    quad.set_billboard(billboard);
+    quad
+}
@@ -0,0 +1,231 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{Matrix4, SquareMatrix};
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::draw_context::{DrawContext, TRANSFORM_UNIFORM_SIZE};
+use crate::textures::CubeTexture;
+
+pub const SKYBOX_SHADER: &str = include_str!("../shaders/skybox.wgsl");
+
+#[rustfmt::skip]
+const VERTICES: [[f32; 3]; 8] = [
+    [-1., -1., -1.], [1., -1., -1.], [1., 1., -1.], [-1., 1., -1.],
+    [-1., -1., 1.], [1., -1., 1.], [1., 1., 1.], [-1., 1., 1.],
+];
+
+#[rustfmt::skip]
+const INDICES: [u16; 36] = [
+    0, 1, 2, 0, 2, 3,
+    5, 4, 7, 5, 7, 6,
+    4, 0, 3, 4, 3, 7,
+    1, 5, 6, 1, 6, 2,
+    3, 2, 6, 3, 6, 7,
+    4, 5, 1, 4, 1, 0,
+];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct SkyboxUniform {
+    camera: [[f32; 4]; 4],
+    far_depth: f32,
+    _padding: [f32; 3],
+}
+
+// Bound with the transform layout, whose minimum binding size is the transform uniform one
+const _: () =
+    assert!(std::mem::size_of::<SkyboxUniform>() as wgpu::BufferAddress == TRANSFORM_UNIFORM_SIZE);
+
+// Cube seen from the inside, which follows the camera rotation but not its translation. It is
+// drawn on the far plane without writing depth, so it can be rendered before or after the scene,
+// rendering it last avoids shading hidden fragments. The camera matrix must be given after each
+// camera update with Skybox::set_camera. Like the drawables, the pipeline has to be refreshed
+// when switching reversed-Z or the sample count, see Skybox::refresh_pipeline.
+pub struct Skybox {
+    texture: CubeTexture,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    far_depth: f32,
+    pipeline_generation: u32,
+}
+
+// Faces are encoded images, in the order +X, -X, +Y, -Y, +Z, -Z
+pub fn create_skybox(context: &DrawContext, faces: [&[u8]; 6]) -> anyhow::Result<Skybox> {
+    let texture = CubeTexture::from_bytes(context, faces)?;
+    Skybox::new(context, texture)
+}
+
+impl Skybox {
+    pub fn new(context: &DrawContext, texture: CubeTexture) -> anyhow::Result<Self> {
+        let device = &context.device;
+        let pipeline = Self::create_pipeline(context)?;
+        let vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Skybox Vertex Buffer"),
+            contents: bytemuck::cast_slice(&VERTICES),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Skybox Index Buffer"),
+            contents: bytemuck::cast_slice(&INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let far_depth = Self::far_depth(context);
+        let uniform_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Skybox Uniform Buffer"),
+            contents: bytemuck::bytes_of(&SkyboxUniform {
+                camera: Matrix4::identity().into(),
+                far_depth,
+                _padding: [0.; 3],
+            }),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Skybox Uniform bind group"),
+            layout: &context.transform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        Ok(Skybox {
+            texture,
+            pipeline,
+            vertex_buffer,
+            index_buffer,
+            uniform_buffer,
+            uniform_bind_group,
+            far_depth,
+            pipeline_generation: context.pipeline_generation(),
+        })
+    }
+
+    fn create_pipeline(context: &DrawContext) -> anyhow::Result<wgpu::RenderPipeline> {
+        let shader_module = context.create_shader_module("Skybox Shader", SKYBOX_SHADER)?;
+        let pipeline_layout =
+            context.create_pipeline_layout(&CubeTexture::bind_group_layout(context));
+        let pipeline = context
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                cache: None,
+                label: Some("Skybox Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: None,
+                    compilation_options: Default::default(),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: None,
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: context.surface_format(),
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                // Seen from the inside, so no culling
+                primitive: wgpu::PrimitiveState {
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                depth_stencil: context.is_depth_enabled().then(|| wgpu::DepthStencilState {
+                    format: context.depth_format(),
+                    depth_write_enabled: false,
+                    depth_compare: context.depth_compare(),
+                    stencil: Default::default(),
+                    bias: Default::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: context.multisample_config.get_multisample_count(),
+                    ..Default::default()
+                },
+                multiview: None,
+            });
+        Ok(pipeline)
+    }
+
+    fn far_depth(context: &DrawContext) -> f32 {
+        match context.is_reverse_z() {
+            true => 0.,
+            false => 1.,
+        }
+    }
+
+    // Creates the pipeline again after DrawContext::set_reverse_z or a sample count change, the
+    // camera must then be given again with set_camera
+    pub fn refresh_pipeline(&mut self, context: &DrawContext) -> anyhow::Result<()> {
+        if self.pipeline_generation == context.pipeline_generation() {
+            return Ok(());
+        }
+        self.pipeline_generation = context.pipeline_generation();
+        self.pipeline = Self::create_pipeline(context)?;
+        self.far_depth = Self::far_depth(context);
+        Ok(())
+    }
+
+    pub fn get_texture(&self) -> &CubeTexture {
+        &self.texture
+    }
+
+    // Expects the camera matrix without translation, see Camera::get_rotation_camera_matrix
+    pub fn set_camera(&self, context: &DrawContext, rotation_camera_matrix: Matrix4<f32>) {
+        context.queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::bytes_of(&SkyboxUniform {
+                camera: rotation_camera_matrix.into(),
+                far_depth: self.far_depth,
+                _padding: [0.; 3],
+            }),
+        );
+    }
+
+    // Overrides the transform and texture bind groups, which the drawables set again anyway
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(
+            DrawContext::BIND_GROUP_INDEX_TRANSFORM,
+            &self.uniform_bind_group,
+            &[0],
+        );
+        render_pass.set_bind_group(
+            DrawContext::BIND_GROUP_INDEX_TEXTURE,
+            self.texture.bind_group(),
+            &[],
+        );
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..1);
+    }
+}
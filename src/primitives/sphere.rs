@@ -0,0 +1,103 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::f32::consts::PI;
+
+use crate::draw_context::Drawable;
+use crate::draw_context::{DrawContext, IndexData, NormalVertex};
+use crate::primitives::{Bounds, Object3D};
+
+const MIN_SEGMENTS: u16 = 3;
+const MIN_RINGS: u16 = 2;
+
+pub struct SphereOptions {
+    pub color: [f32; 3],
+}
+
+impl Default for SphereOptions {
+    fn default() -> Self {
+        SphereOptions {
+            color: [1., 1., 1.],
+        }
+    }
+}
+
+// UV sphere centered on the origin, the poles on the Y axis. Segments are around the Y axis,
+// rings from one pole to the other.
+pub fn create_sphere(
+    context: &DrawContext,
+    vertex_state: wgpu::VertexState,
+    fragment_state: wgpu::FragmentState,
+    segments: u16,
+    rings: u16,
+    radius: f32,
+    options: SphereOptions,
+) -> Object3D {
+    let segments = segments.max(MIN_SEGMENTS);
+    let rings = rings.max(MIN_RINGS);
+    let color = options.color;
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for ring in 0..=rings {
+        let latitude = PI * ring as f32 / rings as f32 - PI / 2.;
+        for i in 0..=segments {
+            let longitude = 2. * PI * i as f32 / segments as f32;
+            let normal = [
+                latitude.cos() * longitude.cos(),
+                latitude.sin(),
+                latitude.cos() * longitude.sin(),
+            ];
+            vertices.push(NormalVertex {
+                position: normal.map(|coordinate| radius * coordinate),
+                color,
+                normal,
+            });
+        }
+    }
+    let ring_size = segments as u32 + 1;
+    for ring in 0..rings as u32 {
+        for i in 0..segments as u32 {
+            let bottom = ring * ring_size + i;
+            let top = bottom + ring_size;
+            indices.push([bottom, bottom + 1, top]);
+            indices.push([top, bottom + 1, top + 1]);
+        }
+    }
+
+    let index_data = IndexData::from_triangles(vertices.len(), indices);
+    let drawable = Drawable::init_indexed_with_options(
+        context,
+        &vertices,
+        &index_data,
+        vertex_state,
+        fragment_state,
+        Default::default(),
+    );
+    let mut sphere = Object3D::from_drawable(drawable);
+    if let Some(bounds) = Bounds::from_positions(vertices.iter().map(|vertex| vertex.position)) {
+        sphere.set_local_bounds(bounds);
+    }
+    sphere
+}
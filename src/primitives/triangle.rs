@@ -24,7 +24,7 @@ SOFTWARE.
 
 use crate::draw_context::Drawable;
 use crate::draw_context::{DrawContext, Vertex};
-use crate::primitives::Object3D;
+use crate::primitives::{Bounds, Object3D};
 
 const TRIANGLE: [Vertex; 3] = [
     Vertex {
@@ -47,5 +47,9 @@ pub fn create_triangle(
     fragment_state: wgpu::FragmentState,
 ) -> Object3D {
     let drawable = Drawable::init_direct(context, &TRIANGLE, vertex_state, fragment_state);
-    Object3D::from_drawable(drawable)
+    let mut triangle = Object3D::from_drawable(drawable);
+    if let Some(bounds) = Bounds::from_positions(TRIANGLE.map(|vertex| vertex.position)) {
+        triangle.set_local_bounds(bounds);
+    }
+    triangle
 }
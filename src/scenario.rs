@@ -22,16 +22,495 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use crate::draw_context::DrawContext;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+
+use crate::cameras::WinitCameraAdapter;
+use crate::draw_context::{DrawContext, RenderOverlay, Viewport};
+use crate::input::InputState;
+use crate::post_process::PostProcess;
+use cgmath::{Matrix4, SquareMatrix};
+use log::{error, info};
 use web_time::{Duration, Instant};
+use winit::keyboard::KeyCode;
 
 pub struct UpdateInterval {
     pub scenario_start: Instant,
+    pub scenario_elapsed: Duration,
     pub update_delta: Duration,
+    pub fps: f32,
+    pub average_fps: f32,
+    pub camera_view: Matrix4<f32>,
+    // Projection and view combined, as sent to the shaders
+    pub camera_matrix: Matrix4<f32>,
+    pub last_render_stats: RenderStats,
+    // None when the cursor is outside of the window
+    pub cursor: Option<CursorPosition>,
+    pub input: InputState,
+}
+
+impl UpdateInterval {
+    // Without input, camera nor render stats, to call DynScenario::update outside of the app, e.g.
+    // with the instants of a ManualClock
+    pub fn new(scenario_start: Instant, now: Instant, update_delta: Duration) -> Self {
+        let fps = match update_delta.is_zero() {
+            true => 0.,
+            false => 1. / update_delta.as_secs_f32(),
+        };
+        UpdateInterval {
+            scenario_start,
+            scenario_elapsed: now.saturating_duration_since(scenario_start),
+            update_delta,
+            fps,
+            average_fps: fps,
+            camera_view: Matrix4::identity(),
+            camera_matrix: Matrix4::identity(),
+            last_render_stats: RenderStats::default(),
+            cursor: None,
+            input: InputState::new(),
+        }
+    }
+}
+
+// Winit reports physical pixels, the same unit as the surface, so no scale factor has to be
+// applied to compare with the surface size
+#[derive(Clone, Copy, Debug)]
+pub struct CursorPosition {
+    // Physical pixels, from the top-left corner
+    pub pixel: [f32; 2],
+    // Normalized device coordinates, from (-1, -1) at the bottom-left corner to (1, 1)
+    pub ndc: [f32; 2],
+}
+
+impl CursorPosition {
+    pub fn new(pixel: [f32; 2], width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        CursorPosition {
+            pixel,
+            ndc: [2.0 * pixel[0] / width - 1.0, 1.0 - 2.0 * pixel[1] / height],
+        }
+    }
+}
+
+// Filled by DynScenario::render, e.g. to display in an overlay. A drawable that cannot be rendered,
+// like a pipeline still being reloaded, is recorded as skipped instead of drawn
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderStats {
+    pub draw_count: u32,
+    pub skipped_count: u32,
+}
+
+impl RenderStats {
+    pub fn record_draw(&mut self) {
+        self.draw_count += 1;
+    }
+
+    pub fn record_skipped(&mut self) {
+        self.skipped_count += 1;
+    }
+}
+
+// Scenario built by the app with Scenario::new, its hooks being those of DynScenario
+pub trait Scenario: DynScenario + Sized {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self>;
 }
 
-pub trait Scenario {
-    fn new(draw_context: &DrawContext) -> Self;
+// Hooks of a scenario, object-safe so scenarios can be boxed and switched, see
+// ScenarioSwitcher. Types built otherwise than with Scenario::new, e.g. an AsyncScenario, only
+// implement this trait.
+pub trait DynScenario {
     fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval);
-    fn render<'drawable>(&'drawable self, render_pass: &mut wgpu::RenderPass<'drawable>);
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    );
+    // Only called when DrawContext::set_depth_prepass is enabled, to render the opaque drawables
+    // with Drawable::render_depth_prepass, see Scene3D::render_depth_prepass
+    fn render_depth_prepass<'drawable>(
+        &'drawable self,
+        _render_pass: &mut wgpu::RenderPass<'drawable>,
+    ) {
+    }
+    // To return false when all the drawables are built without depth test, so the render pass
+    // has no depth attachment
+    fn uses_depth(&self) -> bool {
+        true
+    }
+    // Rendered over the scene, e.g. a TextLayer
+    fn overlay(&mut self) -> Option<&mut dyn RenderOverlay> {
+        None
+    }
+    // Applied to the rendered scene, before the overlays
+    fn post_process(&self) -> Option<&PostProcess> {
+        None
+    }
+    // Clear color of the frame, the context clear color is used when None
+    fn background(&self) -> Option<wgpu::Color> {
+        None
+    }
+    // Only used with RedrawMode::OnDemand, where frames are otherwise only rendered after input
+    // events. To return true while something is animated.
+    fn needs_redraw(&self) -> bool {
+        false
+    }
+    // Camera driven by the input and used for the projection, instead of the camera of the app
+    // when Some. A scenario with several cameras returns the active one, and renders with the
+    // others by itself, e.g. with DrawContext::set_projection. Only the active camera follows the
+    // window aspect ratio, see DrawContext::aspect_ratio for the initial one.
+    fn active_camera(&mut self) -> Option<&mut WinitCameraAdapter> {
+        None
+    }
+    // Renders the scenario once per viewport, e.g. for a split-screen, see
+    // DrawContext::render_scene_with_viewports. A Scene3D must then be given the viewport cameras,
+    // see Scene3D::update_frustums.
+    fn viewports(&self) -> Option<&[Viewport]> {
+        None
+    }
+    // Camera matrix of the scenario rendered as a layer of DrawContext::render_layers, e.g. the
+    // identity for a HUD drawn in clip space, instead of the camera set with
+    // DrawContext::set_projection. Ignored when rendering with viewports. A Scene3D must then be
+    // given that camera, see Scene3D::update_frustums.
+    fn layer_camera_matrix(&self) -> Option<Matrix4<f32>> {
+        None
+    }
+    #[cfg(feature = "egui")]
+    fn on_gui(&mut self, _ctx: &egui::Context) {}
+}
+
+pub type ScenarioBuilder = fn(&DrawContext) -> anyhow::Result<Box<dyn DynScenario>>;
+
+// To be used as a ScenarioBuilder, e.g. `build_scenario::<MyScenario>`
+pub fn build_scenario<S: Scenario + 'static>(
+    context: &DrawContext,
+) -> anyhow::Result<Box<dyn DynScenario>> {
+    Ok(Box::new(S::new(context)?))
+}
+
+pub trait ScenarioCollection {
+    fn builders() -> Vec<(&'static str, ScenarioBuilder)>;
+}
+
+// Runs the first scenario of the collection, the digit keys 1 to 9 switching to the other ones.
+// The previous scenario is dropped before the next one is built, which releases its GPU
+// resources, and is rebuilt if the next one fails to build. The camera belongs to the app, so it is
+// shared between the scenarios not providing their own, see DynScenario::active_camera.
+pub struct ScenarioSwitcher<C> {
+    builders: Vec<(&'static str, ScenarioBuilder)>,
+    active_index: usize,
+    active: Option<Box<dyn DynScenario>>,
+    collection: std::marker::PhantomData<C>,
+}
+
+impl<C: ScenarioCollection> ScenarioSwitcher<C> {
+    const SWITCH_KEYS: [KeyCode; 9] = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+
+    pub fn active_index(&self) -> usize {
+        self.active_index
+    }
+
+    pub fn switch_to(&mut self, context: &DrawContext, index: usize) {
+        let Some(&(name, builder)) = self.builders.get(index) else {
+            return;
+        };
+        if index == self.active_index && self.active.is_some() {
+            return;
+        }
+        // Released first, so that two scenarios never hold GPU resources at the same time
+        self.active = None;
+        match builder(context) {
+            Ok(scenario) => {
+                info!("Switched to scenario {name}");
+                self.active = Some(scenario);
+                self.active_index = index;
+            }
+            Err(err) => {
+                error!("Could not build scenario {name}: {err:#}");
+                let (previous_name, previous_builder) = self.builders[self.active_index];
+                self.active = previous_builder(context)
+                    .inspect_err(|err| error!("Could not rebuild {previous_name}: {err:#}"))
+                    .ok();
+            }
+        }
+    }
+}
+
+impl<C: ScenarioCollection> Scenario for ScenarioSwitcher<C> {
+    fn new(draw_context: &DrawContext) -> anyhow::Result<Self> {
+        let builders = C::builders();
+        let Some(&(_, builder)) = builders.first() else {
+            anyhow::bail!("The scenario collection is empty");
+        };
+        let active = Some(builder(draw_context)?);
+        Ok(ScenarioSwitcher {
+            builders,
+            active_index: 0,
+            active,
+            collection: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<C: ScenarioCollection> DynScenario for ScenarioSwitcher<C> {
+    fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        let requested_index = Self::SWITCH_KEYS
+            .iter()
+            .position(|key| update_interval.input.just_pressed(*key));
+        if let Some(index) = requested_index {
+            self.switch_to(context, index);
+        }
+        if let Some(ref mut active) = self.active {
+            active.update(context, update_interval);
+        }
+    }
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        if let Some(ref active) = self.active {
+            active.render(render_pass, render_stats);
+        }
+    }
+    fn render_depth_prepass<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+    ) {
+        if let Some(ref active) = self.active {
+            active.render_depth_prepass(render_pass);
+        }
+    }
+    fn uses_depth(&self) -> bool {
+        self.active
+            .as_ref()
+            .is_none_or(|active| active.uses_depth())
+    }
+    fn overlay(&mut self) -> Option<&mut dyn RenderOverlay> {
+        self.active.as_mut()?.overlay()
+    }
+    fn post_process(&self) -> Option<&PostProcess> {
+        self.active.as_ref()?.post_process()
+    }
+    fn background(&self) -> Option<wgpu::Color> {
+        self.active.as_ref()?.background()
+    }
+    fn needs_redraw(&self) -> bool {
+        self.active
+            .as_ref()
+            .is_some_and(|active| active.needs_redraw())
+    }
+    fn active_camera(&mut self) -> Option<&mut WinitCameraAdapter> {
+        self.active.as_mut()?.active_camera()
+    }
+    fn viewports(&self) -> Option<&[Viewport]> {
+        self.active.as_ref()?.viewports()
+    }
+    fn layer_camera_matrix(&self) -> Option<Matrix4<f32>> {
+        self.active.as_ref()?.layer_camera_matrix()
+    }
+    #[cfg(feature = "egui")]
+    fn on_gui(&mut self, ctx: &egui::Context) {
+        if let Some(ref mut active) = self.active {
+            active.on_gui(ctx);
+        }
+    }
+}
+
+// Scenario whose assets are loaded asynchronously, e.g. textures or models fetched over the
+// network in the browser, without blocking the main thread. The assets are loaded without the
+// context, the GPU resources being created from them in build. To be run with AsyncLoader, the
+// scenario only implementing the hooks of DynScenario, as it is not built with Scenario::new.
+pub trait AsyncScenario: Sized {
+    type Assets: 'static;
+    fn load() -> impl Future<Output = anyhow::Result<Self::Assets>> + 'static;
+    fn build(draw_context: &DrawContext, assets: Self::Assets) -> anyhow::Result<Self>;
+}
+
+enum LoadState<S: AsyncScenario> {
+    Loading(Pin<Box<dyn Future<Output = anyhow::Result<S::Assets>>>>),
+    Ready(S),
+    Failed,
+}
+
+// Polls the assets of the scenario on each update, rendering only the loading background until
+// they are ready. The scenario is built on the update where the assets resolve, and receives its
+// first update on the next one. The scenario elapsed time includes the loading.
+pub struct AsyncLoader<S: AsyncScenario> {
+    state: LoadState<S>,
+    loading_background: Option<wgpu::Color>,
+}
+
+impl<S: AsyncScenario + DynScenario> AsyncLoader<S> {
+    // The context clear color is used when None
+    pub fn set_loading_background(&mut self, color: Option<wgpu::Color>) {
+        self.loading_background = color;
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, LoadState::Loading(_))
+    }
+
+    pub fn scenario(&self) -> Option<&S> {
+        match self.state {
+            LoadState::Ready(ref scenario) => Some(scenario),
+            _ => None,
+        }
+    }
+
+    pub fn scenario_mut(&mut self) -> Option<&mut S> {
+        match self.state {
+            LoadState::Ready(ref mut scenario) => Some(scenario),
+            _ => None,
+        }
+    }
+
+    // Wakers are not used, the future being polled again on each update
+    fn poll_assets(&mut self, context: &DrawContext) {
+        let LoadState::Loading(ref mut assets) = self.state else {
+            return;
+        };
+        let mut task_context = std::task::Context::from_waker(std::task::Waker::noop());
+        let Poll::Ready(assets) = assets.as_mut().poll(&mut task_context) else {
+            return;
+        };
+        self.state = match assets.and_then(|assets| S::build(context, assets)) {
+            Ok(scenario) => {
+                info!("Scenario assets loaded");
+                LoadState::Ready(scenario)
+            }
+            Err(err) => {
+                error!("Could not load the scenario: {err:#}");
+                LoadState::Failed
+            }
+        };
+    }
+}
+
+impl<S: AsyncScenario + DynScenario> Scenario for AsyncLoader<S> {
+    fn new(_draw_context: &DrawContext) -> anyhow::Result<Self> {
+        Ok(AsyncLoader {
+            state: LoadState::Loading(Box::pin(S::load())),
+            loading_background: None,
+        })
+    }
+}
+
+impl<S: AsyncScenario + DynScenario> DynScenario for AsyncLoader<S> {
+    fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        match self.state {
+            LoadState::Loading(_) => self.poll_assets(context),
+            LoadState::Ready(ref mut scenario) => scenario.update(context, update_interval),
+            LoadState::Failed => {}
+        }
+    }
+    fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        if let Some(scenario) = self.scenario() {
+            scenario.render(render_pass, render_stats);
+        }
+    }
+    fn render_depth_prepass<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+    ) {
+        if let Some(scenario) = self.scenario() {
+            scenario.render_depth_prepass(render_pass);
+        }
+    }
+    fn uses_depth(&self) -> bool {
+        self.scenario()
+            .is_some_and(|scenario| scenario.uses_depth())
+    }
+    fn overlay(&mut self) -> Option<&mut dyn RenderOverlay> {
+        self.scenario_mut()?.overlay()
+    }
+    fn post_process(&self) -> Option<&PostProcess> {
+        self.scenario()?.post_process()
+    }
+    fn background(&self) -> Option<wgpu::Color> {
+        match self.scenario() {
+            Some(scenario) => scenario.background(),
+            None => self.loading_background,
+        }
+    }
+    // Updates keep polling the assets with RedrawMode::OnDemand
+    fn needs_redraw(&self) -> bool {
+        match self.state {
+            LoadState::Loading(_) => true,
+            LoadState::Ready(ref scenario) => scenario.needs_redraw(),
+            LoadState::Failed => false,
+        }
+    }
+    fn active_camera(&mut self) -> Option<&mut WinitCameraAdapter> {
+        self.scenario_mut()?.active_camera()
+    }
+    fn viewports(&self) -> Option<&[Viewport]> {
+        self.scenario()?.viewports()
+    }
+    fn layer_camera_matrix(&self) -> Option<Matrix4<f32>> {
+        self.scenario()?.layer_camera_matrix()
+    }
+    #[cfg(feature = "egui")]
+    fn on_gui(&mut self, ctx: &egui::Context) {
+        if let Some(scenario) = self.scenario_mut() {
+            scenario.on_gui(ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only implements the hooks, without Scenario::new
+    struct LoadedScenario(u32);
+
+    impl AsyncScenario for LoadedScenario {
+        type Assets = u32;
+        async fn load() -> anyhow::Result<Self::Assets> {
+            Ok(42)
+        }
+        fn build(_draw_context: &DrawContext, assets: Self::Assets) -> anyhow::Result<Self> {
+            Ok(LoadedScenario(assets))
+        }
+    }
+
+    impl DynScenario for LoadedScenario {
+        fn update(&mut self, _context: &DrawContext, _update_interval: &UpdateInterval) {}
+        fn render<'drawable>(
+            &'drawable self,
+            _render_pass: &mut wgpu::RenderPass<'drawable>,
+            _render_stats: &mut RenderStats,
+        ) {
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a GPU adapter"]
+    fn async_scenario_is_built_without_scenario_new() {
+        let context = pollster::block_on(DrawContext::new_headless(4, 4)).unwrap();
+        let mut loader = <AsyncLoader<LoadedScenario> as Scenario>::new(&context).unwrap();
+        assert!(loader.is_loading());
+        let now = Instant::now();
+        DynScenario::update(
+            &mut loader,
+            &context,
+            &UpdateInterval::new(now, now, Duration::ZERO),
+        );
+        assert_eq!(loader.scenario().map(|scenario| scenario.0), Some(42));
+    }
 }
@@ -0,0 +1,319 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+use cgmath::Matrix4;
+use log::error;
+
+use crate::draw_context::{DrawContext, Drawable};
+use crate::frustum::Frustum;
+use crate::primitives::Object3D;
+use crate::scenario::{RenderStats, UpdateInterval};
+
+pub type Animation = Box<dyn FnMut(&DrawContext, &UpdateInterval, &mut Object3D)>;
+
+// Scene members updating themselves on each Scene3D::update, e.g. an Object3DInstanceGroup
+pub trait SceneUpdate: AsRef<Drawable> {
+    fn scene_update(&mut self, context: &DrawContext, update_interval: &UpdateInterval);
+}
+
+// Container rendering a list of objects, skipping those outside of the camera frustum
+pub struct Scene3D {
+    objects: Vec<Object3D>,
+    // Object index and its animation, called on each update
+    animations: Vec<(usize, Animation)>,
+    // Not frustum culled, having no bounds
+    updatables: Vec<Box<dyn SceneUpdate>>,
+    // Objects outside of all the frustums are culled, one per camera the scene is rendered with
+    frustums: Vec<Frustum>,
+    frustum_culling: bool,
+    // Camera view of the last update, used to sort the opaque objects
+    camera_view: Option<Matrix4<f32>>,
+    opaque_sorting: bool,
+    culled_count: Cell<usize>,
+}
+
+impl Default for Scene3D {
+    fn default() -> Self {
+        Scene3D {
+            objects: Vec::new(),
+            animations: Vec::new(),
+            updatables: Vec::new(),
+            frustums: Vec::new(),
+            frustum_culling: true,
+            camera_view: None,
+            opaque_sorting: false,
+            culled_count: Cell::new(0),
+        }
+    }
+}
+
+impl Scene3D {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Returns the index of the object in the scene
+    pub fn add(&mut self, object: Object3D) -> usize {
+        self.objects.push(object);
+        self.objects.len() - 1
+    }
+
+    // Same as add, the animation being applied to the object on each Scene3D::update
+    pub fn add_animated(
+        &mut self,
+        object: Object3D,
+        animation: impl FnMut(&DrawContext, &UpdateInterval, &mut Object3D) + 'static,
+    ) -> usize {
+        let index = self.add(object);
+        self.animations.push((index, Box::new(animation)));
+        index
+    }
+
+    pub fn add_updatable(&mut self, updatable: impl SceneUpdate + 'static) {
+        self.updatables.push(Box::new(updatable));
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Object3D> {
+        self.objects.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Object3D> {
+        self.objects.get_mut(index)
+    }
+
+    pub fn objects(&self) -> &[Object3D] {
+        &self.objects
+    }
+
+    pub fn objects_mut(&mut self) -> &mut [Object3D] {
+        &mut self.objects
+    }
+
+    pub fn set_frustum_culling(&mut self, enabled: bool) {
+        self.frustum_culling = enabled;
+    }
+
+    pub fn is_frustum_culling_enabled(&self) -> bool {
+        self.frustum_culling
+    }
+
+    // Opaque objects are rendered front-to-back, reducing overdraw with the early depth test, and
+    // followed by the other objects in insertion order. Only objects with bounds and without
+    // blending are sorted, see Drawable::is_opaque. Disabled by default, to keep the draw order
+    // deterministic.
+    pub fn set_opaque_sorting(&mut self, enabled: bool) {
+        self.opaque_sorting = enabled;
+    }
+
+    pub fn is_opaque_sorting_enabled(&self) -> bool {
+        self.opaque_sorting
+    }
+
+    // To be called on each update, e.g. with UpdateInterval::camera_view
+    pub fn update_camera_view(&mut self, camera_view: &Matrix4<f32>) {
+        self.camera_view = Some(*camera_view);
+    }
+
+    // To be called on each update, e.g. with UpdateInterval::camera_matrix
+    pub fn update_frustum(&mut self, camera_matrix: &Matrix4<f32>) {
+        self.update_frustums(std::slice::from_ref(camera_matrix));
+    }
+
+    // For a scene rendered with other cameras than the one of UpdateInterval, to be called after
+    // Scene3D::update with the camera matrix of each viewport, see DynScenario::viewports, or with
+    // the one of DynScenario::layer_camera_matrix. Objects are culled when outside of all of them.
+    pub fn update_frustums(&mut self, camera_matrices: &[Matrix4<f32>]) {
+        self.frustums = camera_matrices
+            .iter()
+            .map(Frustum::from_camera_matrix)
+            .collect();
+    }
+
+    // Runs the object animations and scene updates, and updates the camera view and frustum used
+    // for sorting and culling, see update_frustums for other cameras. Also refreshes the pipelines
+    // of the objects, see Drawable::refresh_pipelines.
+    pub fn update(&mut self, context: &DrawContext, update_interval: &UpdateInterval) {
+        for object in &mut self.objects {
+            if let Err(err) = object.as_mut().refresh_pipelines(context) {
+                error!("Could not refresh the pipelines of an object: {err:#}");
+            }
+        }
+        for (index, animation) in &mut self.animations {
+            animation(context, update_interval, &mut self.objects[*index]);
+        }
+        for updatable in &mut self.updatables {
+            updatable.scene_update(context, update_interval);
+        }
+        self.update_camera_view(&update_interval.camera_view);
+        self.update_frustum(&update_interval.camera_matrix);
+    }
+
+    // Number of objects skipped during the last render
+    pub fn culled_count(&self) -> usize {
+        self.culled_count.get()
+    }
+
+    fn is_culled(&self, object: &Object3D) -> bool {
+        if !self.frustum_culling {
+            return false;
+        }
+        let Some(bounds) = object.world_bounds() else {
+            return false;
+        };
+        !self.frustums.is_empty()
+            && !self
+                .frustums
+                .iter()
+                .any(|frustum| frustum.intersects_sphere(bounds.center(), bounds.radius()))
+    }
+
+    // Distance along the view direction, None for the objects not sorted
+    fn opaque_depth(object: &Object3D, camera_view: &Matrix4<f32>) -> Option<f32> {
+        if !object.as_ref().is_opaque() {
+            return None;
+        }
+        let center = object.world_bounds()?.center();
+        Some((camera_view * center.to_homogeneous()).z)
+    }
+
+    // In insertion order, or with the opaque objects first when sorted
+    fn for_each_object<'a>(&'a self, mut visit: impl FnMut(&'a Object3D)) {
+        let Some(camera_view) = self.camera_view.filter(|_| self.opaque_sorting) else {
+            self.objects.iter().for_each(visit);
+            return;
+        };
+        let mut sorted: Vec<_> = self
+            .objects
+            .iter()
+            .map(|object| (Self::opaque_depth(object, &camera_view), object))
+            .collect();
+        // Stable, so the objects not sorted keep their insertion order
+        sorted.sort_by(|(depth_a, _), (depth_b, _)| match (depth_a, depth_b) {
+            (Some(depth_a), Some(depth_b)) => depth_a.total_cmp(depth_b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        for (_, object) in sorted {
+            visit(object);
+        }
+    }
+
+    // Culled objects are skipped, as in render
+    pub fn render_depth_prepass<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+    ) {
+        self.for_each_object(|object| {
+            if !self.is_culled(object) {
+                object.as_ref().render_depth_prepass(render_pass);
+            }
+        });
+        for updatable in &self.updatables {
+            (**updatable).as_ref().render_depth_prepass(render_pass);
+        }
+    }
+
+    pub fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        let mut culled_count = 0;
+        self.for_each_object(|object| {
+            if self.is_culled(object) {
+                culled_count += 1;
+                render_stats.record_skipped();
+                return;
+            }
+            object.as_ref().render(render_pass);
+            render_stats.record_draw();
+        });
+        let updatables = self
+            .updatables
+            .iter()
+            .map(|updatable| (**updatable).as_ref());
+        render_drawables(updatables, render_pass, render_stats);
+        self.culled_count.set(culled_count);
+    }
+}
+
+// Render path shared by the scenes, for drawables which are always drawn
+fn render_drawables<'drawable>(
+    drawables: impl IntoIterator<Item = &'drawable Drawable>,
+    render_pass: &mut wgpu::RenderPass<'drawable>,
+    render_stats: &mut RenderStats,
+) {
+    for drawable in drawables {
+        drawable.render(render_pass);
+        render_stats.record_draw();
+    }
+}
+
+// Alternative to Scene3D for scenes not changing after their setup, e.g. canvas demos. The
+// drawables are stored and rendered as is, in insertion order, without animation nor culling.
+#[derive(Default)]
+pub struct StaticScene {
+    drawables: Vec<Drawable>,
+}
+
+impl StaticScene {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Returns the index of the drawable in the scene, an Object3D being added with its drawable
+    pub fn add(&mut self, drawable: impl Into<Drawable>) -> usize {
+        self.drawables.push(drawable.into());
+        self.drawables.len() - 1
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Drawable> {
+        self.drawables.get(index)
+    }
+
+    pub fn drawables(&self) -> &[Drawable] {
+        &self.drawables
+    }
+
+    pub fn render_depth_prepass<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+    ) {
+        for drawable in &self.drawables {
+            drawable.render_depth_prepass(render_pass);
+        }
+    }
+
+    pub fn render<'drawable>(
+        &'drawable self,
+        render_pass: &mut wgpu::RenderPass<'drawable>,
+        render_stats: &mut RenderStats,
+    ) {
+        render_drawables(&self.drawables, render_pass, render_stats);
+    }
+}
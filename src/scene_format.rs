@@ -0,0 +1,319 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+// Scene description loaded from a JSON file, e.g.:
+//
+// {
+//   "camera": { "eye": [0, 2, -5], "target": [0, 0, 0] },
+//   "objects": [
+//     { "primitive": "cube", "position": [-1, 0, 0], "rotation": [0, 45, 0] },
+//     { "primitive": { "cylinder": { "segments": 32, "height": 1, "radius": 0.3 } },
+//       "color": [1, 0.5, 0], "position": [1, 0, 0] },
+//     { "primitive": { "sphere": { "segments": 32, "rings": 16, "radius": 0.5 } },
+//       "position": [0, 1, 0] }
+//   ]
+// }
+//
+// Rotations are Euler angles in degrees, applied around X, then Y, then Z. Colors only apply to
+// the lit primitives, white by default. Cubes and triangles keep their vertex colors, a color
+// given to them is rejected.
+
+use anyhow::{bail, Context};
+use cgmath::{Deg, Matrix4, Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::cameras::Camera;
+use crate::draw_context::DrawContext;
+use crate::lights::LAMBERT_SHADER;
+use crate::primitives::cone::{self, ConeOptions};
+use crate::primitives::cylinder::{self, CylinderOptions};
+use crate::primitives::sphere::{self, SphereOptions};
+use crate::primitives::{cube, triangle, Object3D};
+use crate::scene::Scene3D;
+
+const DEFAULT_SHADER: &str = include_str!("shaders/default.wgsl");
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SceneDescription {
+    #[serde(default)]
+    pub camera: Option<SceneCamera>,
+    #[serde(default)]
+    pub objects: Vec<SceneObject>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneCamera {
+    pub eye: [f32; 3],
+    pub target: [f32; 3],
+    #[serde(default = "default_up")]
+    pub up: [f32; 3],
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneObject {
+    pub primitive: ScenePrimitive,
+    #[serde(default)]
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    #[serde(default = "default_scale")]
+    pub scale: [f32; 3],
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenePrimitive {
+    Cube,
+    Triangle,
+    Cylinder {
+        segments: u16,
+        height: f32,
+        radius: f32,
+    },
+    Cone {
+        segments: u16,
+        height: f32,
+        radius: f32,
+    },
+    Sphere {
+        segments: u16,
+        rings: u16,
+        radius: f32,
+    },
+}
+
+impl ScenePrimitive {
+    // Drawn with the Lambert shader, in the color of the object
+    fn is_lit(&self) -> bool {
+        match self {
+            ScenePrimitive::Cube | ScenePrimitive::Triangle => false,
+            ScenePrimitive::Cylinder { .. }
+            | ScenePrimitive::Cone { .. }
+            | ScenePrimitive::Sphere { .. } => true,
+        }
+    }
+}
+
+fn default_up() -> [f32; 3] {
+    [0., 1., 0.]
+}
+
+fn default_scale() -> [f32; 3] {
+    [1., 1., 1.]
+}
+
+fn default_color() -> [f32; 3] {
+    [1., 1., 1.]
+}
+
+impl SceneCamera {
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.look_at(
+            Point3::from(self.eye),
+            Point3::from(self.target),
+            Vector3::from(self.up),
+        );
+    }
+}
+
+impl SceneObject {
+    fn color(&self) -> [f32; 3] {
+        self.color.unwrap_or_else(default_color)
+    }
+
+    fn transform(&self) -> Matrix4<f32> {
+        let [x, y, z] = self.rotation;
+        Matrix4::from_translation(Vector3::from(self.position))
+            * Matrix4::from_angle_z(Deg(z))
+            * Matrix4::from_angle_y(Deg(y))
+            * Matrix4::from_angle_x(Deg(x))
+            * Matrix4::from_nonuniform_scale(self.scale[0], self.scale[1], self.scale[2])
+    }
+}
+
+impl SceneDescription {
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let description: Self = serde_json::from_str(json).context("Invalid scene description")?;
+        description.validate()?;
+        Ok(description)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        for (index, object) in self.objects.iter().enumerate() {
+            if object.color.is_some() && !object.primitive.is_lit() {
+                bail!(
+                    "Object {index}: {:?} primitives keep their vertex colors, color is not allowed",
+                    object.primitive
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Objects are created with the default shader, or the Lambert shader for the lit primitives
+    pub fn instantiate(&self, context: &DrawContext) -> anyhow::Result<Scene3D> {
+        self.validate()?;
+        let default_module =
+            context.create_shader_module("Scene Default Shader", DEFAULT_SHADER)?;
+        let lambert_module =
+            context.create_shader_module("Scene Lambert Shader", LAMBERT_SHADER)?;
+        let targets = [Some(wgpu::ColorTargetState {
+            format: context.surface_format(),
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
+        let default_buffers = [context.vertex_buffer_layout.clone()];
+        let normal_buffers = [context.normal_vertex_buffer_layout.clone()];
+        let mut scene = Scene3D::new();
+        for object in &self.objects {
+            let mut object_3d: Object3D = match object.primitive {
+                ScenePrimitive::Cube => {
+                    let (vertex_state, fragment_state) =
+                        pipeline_states(&default_module, &default_buffers, &targets);
+                    cube::create_cube(context, vertex_state, fragment_state)
+                }
+                ScenePrimitive::Triangle => {
+                    let (vertex_state, fragment_state) =
+                        pipeline_states(&default_module, &default_buffers, &targets);
+                    triangle::create_triangle(context, vertex_state, fragment_state)
+                }
+                ScenePrimitive::Cylinder {
+                    segments,
+                    height,
+                    radius,
+                } => {
+                    let (vertex_state, fragment_state) =
+                        pipeline_states(&lambert_module, &normal_buffers, &targets);
+                    cylinder::create_cylinder(
+                        context,
+                        vertex_state,
+                        fragment_state,
+                        segments,
+                        height,
+                        radius,
+                        CylinderOptions {
+                            color: object.color(),
+                            ..Default::default()
+                        },
+                    )
+                }
+                ScenePrimitive::Cone {
+                    segments,
+                    height,
+                    radius,
+                } => {
+                    let (vertex_state, fragment_state) =
+                        pipeline_states(&lambert_module, &normal_buffers, &targets);
+                    cone::create_cone(
+                        context,
+                        vertex_state,
+                        fragment_state,
+                        segments,
+                        height,
+                        radius,
+                        ConeOptions {
+                            color: object.color(),
+                            ..Default::default()
+                        },
+                    )
+                }
+                ScenePrimitive::Sphere {
+                    segments,
+                    rings,
+                    radius,
+                } => {
+                    let (vertex_state, fragment_state) =
+                        pipeline_states(&lambert_module, &normal_buffers, &targets);
+                    sphere::create_sphere(
+                        context,
+                        vertex_state,
+                        fragment_state,
+                        segments,
+                        rings,
+                        radius,
+                        SphereOptions {
+                            color: object.color(),
+                        },
+                    )
+                }
+            };
+            object_3d.set_transform(context, object.transform());
+            scene.add(object_3d);
+        }
+        Ok(scene)
+    }
+}
+
+fn pipeline_states<'a>(
+    module: &'a wgpu::ShaderModule,
+    buffers: &'a [wgpu::VertexBufferLayout<'static>],
+    targets: &'a [Option<wgpu::ColorTargetState>],
+) -> (wgpu::VertexState<'a>, wgpu::FragmentState<'a>) {
+    let vertex_state = wgpu::VertexState {
+        module,
+        entry_point: None,
+        buffers,
+        compilation_options: Default::default(),
+    };
+    let fragment_state = wgpu::FragmentState {
+        module,
+        entry_point: None,
+        targets,
+        compilation_options: Default::default(),
+    };
+    (vertex_state, fragment_state)
+}
+
+// The camera of the description, if any, is not applied, see SceneCamera::apply
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_scene(
+    context: &DrawContext,
+    path: impl AsRef<std::path::Path>,
+) -> anyhow::Result<Scene3D> {
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot read scene file {}", path.display()))?;
+    SceneDescription::from_json(&json)?.instantiate(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_is_rejected_on_unlit_primitives() {
+        let sphere = r#"{ "objects": [
+            { "primitive": { "sphere": { "segments": 8, "rings": 4, "radius": 1 } },
+              "color": [1, 0, 0] }
+        ] }"#;
+        let description = SceneDescription::from_json(sphere).unwrap();
+        assert_eq!(description.objects[0].color(), [1., 0., 0.]);
+        let cube = r#"{ "objects": [ { "primitive": "cube", "color": [1, 0, 0] } ] }"#;
+        assert!(SceneDescription::from_json(cube).is_err());
+        let cube = r#"{ "objects": [ { "primitive": "cube" } ] }"#;
+        assert!(SceneDescription::from_json(cube).is_ok());
+    }
+}
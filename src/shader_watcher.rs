@@ -0,0 +1,168 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+
+use anyhow::Context;
+use log::{debug, error};
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::draw_context::{DrawContext, Drawable};
+
+// Watches WGSL files and recompiles them when they change on disk.
+// Parent directories are watched, as many editors save by replacing the file.
+pub struct ShaderWatcher {
+    watcher: notify::RecommendedWatcher,
+    receiver: Receiver<notify::Result<notify::Event>>,
+    watched_files: BTreeSet<PathBuf>,
+    watched_dirs: BTreeSet<PathBuf>,
+    // Drawables using each shader, as indices in the slice given to reload_drawables
+    drawables: BTreeMap<PathBuf, Vec<usize>>,
+}
+
+impl ShaderWatcher {
+    pub fn new() -> anyhow::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender).context("Failed to create watcher")?;
+        Ok(ShaderWatcher {
+            watcher,
+            receiver,
+            watched_files: BTreeSet::new(),
+            watched_dirs: BTreeSet::new(),
+            drawables: BTreeMap::new(),
+        })
+    }
+
+    pub fn watch(&mut self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        self.watch_canonical(path).map(|_| ())
+    }
+
+    // Same as watch, the pipelines of the drawables at these indices being replaced when the
+    // shader changes, see reload_drawables
+    pub fn watch_for_drawables(
+        &mut self,
+        path: impl AsRef<Path>,
+        drawable_indices: impl IntoIterator<Item = usize>,
+    ) -> anyhow::Result<()> {
+        let path = self.watch_canonical(path)?;
+        self.drawables
+            .entry(path)
+            .or_default()
+            .extend(drawable_indices);
+        Ok(())
+    }
+
+    fn watch_canonical(&mut self, path: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+        let path = path
+            .as_ref()
+            .canonicalize()
+            .with_context(|| format!("Cannot watch {}", path.as_ref().display()))?;
+        let dir = path
+            .parent()
+            .context("Shader file has no parent directory")?
+            .to_path_buf();
+        if !self.watched_dirs.contains(&dir) {
+            self.watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Cannot watch {}", dir.display()))?;
+            self.watched_dirs.insert(dir);
+        }
+        debug!("Watching shader {}", path.display());
+        self.watched_files.insert(path.clone());
+        Ok(path)
+    }
+
+    // To be called regularly instead of reloaded_shaders, e.g. in DynScenario::update, with the
+    // drawables in the order of the indices given to watch_for_drawables. The reloaded module is
+    // used for both stages, see Drawable::replace_shaders. On error, the previous pipeline is kept.
+    pub fn reload_drawables(&mut self, context: &DrawContext, drawables: &mut [&mut Drawable]) {
+        for (path, shader_module) in self.reloaded_shaders(context) {
+            let Some(indices) = self.drawables.get(&path) else {
+                continue;
+            };
+            for &index in indices {
+                let Some(drawable) = drawables.get_mut(index) else {
+                    error!("No drawable {index} to reload {}", path.display());
+                    continue;
+                };
+                if let Err(err) = drawable.replace_shaders(context, &shader_module, &shader_module)
+                {
+                    error!(
+                        "Could not reload {} in drawable {index}: {err:#}",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    // To be called regularly, e.g. in DynScenario::update. Shaders failing to compile are logged
+    // and skipped, so the caller keeps its last valid pipeline.
+    pub fn reloaded_shaders(
+        &mut self,
+        context: &DrawContext,
+    ) -> Vec<(PathBuf, Rc<wgpu::ShaderModule>)> {
+        let mut changed_files = BTreeSet::new();
+        for event in self.receiver.try_iter() {
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    error!("Shader watcher error: {err}");
+                    continue;
+                }
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+            changed_files.extend(
+                event
+                    .paths
+                    .into_iter()
+                    .filter(|path| self.watched_files.contains(path)),
+            );
+        }
+        changed_files
+            .into_iter()
+            .filter_map(|path| {
+                let label = path.display().to_string();
+                let shader_module = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Cannot read {label}"))
+                    .and_then(|source| context.create_shader_module(&label, &source));
+                match shader_module {
+                    Ok(shader_module) => {
+                        debug!("Reloaded shader {label}");
+                        Some((path, shader_module))
+                    }
+                    Err(err) => {
+                        error!("{err:#}");
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,27 @@
+// Prepended to the shadertoy-like code, which must define:
+// fn main_image(frag_coord: vec2<f32>) -> vec4<f32>
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+};
+
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    out.position = vec4<f32>(vtx_in.position, 1.0);
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    // Shadertoy origin is the bottom-left corner
+    let frag_coord = vec2<f32>(frg_in.position.x, globals.resolution.y - frg_in.position.y);
+    return main_image(frag_coord);
+}
+
@@ -0,0 +1,34 @@
+// Post-process code, see PostProcess
+// params.x: effect, 0 for grayscale, 1 for sepia, 2 for invert
+// params.y: effect strength, from 0 to 1
+// Colors are sampled and written in linear space, the sRGB conversions being done by the
+// texture formats, so mixing them does not shift the colors.
+
+fn grayscale(color: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(dot(color, vec3<f32>(0.2126, 0.7152, 0.0722)));
+}
+
+fn sepia(color: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(
+        dot(color, vec3<f32>(0.393, 0.769, 0.189)),
+        dot(color, vec3<f32>(0.349, 0.686, 0.168)),
+        dot(color, vec3<f32>(0.272, 0.534, 0.131)),
+    );
+}
+
+fn post_process(uv: vec2<f32>) -> vec4<f32> {
+    let color = textureSample(source_texture, source_sampler, uv);
+    var effect_color: vec3<f32>;
+    switch u32(params.x) {
+        case 0u: {
+            effect_color = grayscale(color.rgb);
+        }
+        case 1u: {
+            effect_color = min(sepia(color.rgb), vec3<f32>(1.0));
+        }
+        default: {
+            effect_color = vec3<f32>(1.0) - color.rgb;
+        }
+    }
+    return vec4<f32>(mix(color.rgb, effect_color, clamp(params.y, 0.0, 1.0)), color.a);
+}
@@ -0,0 +1,59 @@
+// FXAA post-process, see AntiAliasing::Fxaa
+// Simplified version of FXAA by Timothy Lottes: the edge direction is estimated from the luma of
+// the 4 diagonal neighbours, then the color is blurred along that direction.
+
+const FXAA_REDUCE_MIN: f32 = 1.0 / 128.0;
+const FXAA_REDUCE_MUL: f32 = 1.0 / 8.0;
+const FXAA_SPAN_MAX: f32 = 8.0;
+
+// Colors are linear, the luma is computed on approximately gamma encoded values, closer to how
+// the edges are perceived
+fn luma(color: vec3<f32>) -> f32 {
+    return dot(sqrt(color), vec3<f32>(0.299, 0.587, 0.114));
+}
+
+fn sample_rgb(uv: vec2<f32>) -> vec3<f32> {
+    return textureSample(source_texture, source_sampler, uv).rgb;
+}
+
+fn post_process(uv: vec2<f32>) -> vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    let color = textureSample(source_texture, source_sampler, uv);
+    let luma_nw = luma(sample_rgb(uv + vec2<f32>(-1.0, -1.0) * texel));
+    let luma_ne = luma(sample_rgb(uv + vec2<f32>(1.0, -1.0) * texel));
+    let luma_sw = luma(sample_rgb(uv + vec2<f32>(-1.0, 1.0) * texel));
+    let luma_se = luma(sample_rgb(uv + vec2<f32>(1.0, 1.0) * texel));
+    let luma_m = luma(color.rgb);
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    var direction = vec2<f32>(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    );
+    let direction_reduce = max(
+        (luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * FXAA_REDUCE_MUL,
+        FXAA_REDUCE_MIN,
+    );
+    let inverse_direction_min = 1.0 / (min(abs(direction.x), abs(direction.y)) + direction_reduce);
+    direction = clamp(
+        direction * inverse_direction_min,
+        vec2<f32>(-FXAA_SPAN_MAX),
+        vec2<f32>(FXAA_SPAN_MAX),
+    ) * texel;
+
+    let rgb_a = 0.5 * (
+        sample_rgb(uv + direction * (1.0 / 3.0 - 0.5)) +
+        sample_rgb(uv + direction * (2.0 / 3.0 - 0.5))
+    );
+    let rgb_b = rgb_a * 0.5 + 0.25 * (
+        sample_rgb(uv - direction * 0.5) +
+        sample_rgb(uv + direction * 0.5)
+    );
+    // The wider blur is discarded when it brings colors from outside the local range
+    let luma_b = luma(rgb_b);
+    if luma_b < luma_min || luma_b > luma_max {
+        return vec4<f32>(rgb_a, color.a);
+    }
+    return vec4<f32>(rgb_b, color.a);
+}
@@ -0,0 +1,45 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+struct InstanceInput {
+    @location(5) model_0: vec4<f32>,
+    @location(6) model_1: vec4<f32>,
+    @location(7) model_2: vec4<f32>,
+    @location(8) model_3: vec4<f32>,
+};
+
+struct FragmentInput {
+    @location(0) color: vec3<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct TransformUniform {
+    m: mat4x4<f32>,
+};
+@group(1) @binding(0)
+var<uniform> transform: TransformUniform;
+
+@group(0) @binding(0)
+var<uniform> camera: TransformUniform;
+
+
+@vertex
+fn vtx_main(vtx_in: VertexInput, instance_in: InstanceInput) -> FragmentInput {
+    let model = mat4x4<f32>(
+        instance_in.model_0,
+        instance_in.model_1,
+        instance_in.model_2,
+        instance_in.model_3,
+    );
+    var out: FragmentInput;
+    out.color = vtx_in.color;
+    out.position = camera.m * transform.m * model * vec4<f32>(vtx_in.position, 1.0);
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    return vec4<f32>(frg_in.color, 1.0);
+}
@@ -0,0 +1,44 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) normal: vec3<f32>,
+};
+
+struct FragmentInput {
+    @location(0) color: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct TransformUniform {
+    m: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: TransformUniform;
+
+@group(1) @binding(0)
+var<uniform> transform: TransformUniform;
+
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    out.color = vtx_in.color;
+    // Valid as long as the transform only has uniform scaling
+    out.normal = (transform.m * vec4<f32>(vtx_in.normal, 0.0)).xyz;
+    out.position = camera.m * transform.m * vec4<f32>(vtx_in.position, 1.0);
+    return out;
+}
+
+@fragment
+fn frg_main(
+    frg_in: FragmentInput,
+    @builtin(front_facing) front_facing: bool,
+) -> @location(0) vec4<f32> {
+    // Back faces are only rendered by double-sided drawables, and are lit from their side
+    let normal = select(-1.0, 1.0, front_facing) * normalize(frg_in.normal);
+    let diffuse = max(dot(normal, -normalize(light.direction)), 0.0);
+    let lighting = light.ambient + diffuse * light.color;
+    return vec4<f32>(frg_in.color * lighting, 1.0);
+}
@@ -0,0 +1,42 @@
+// Debug view of the normals as colors, each component mapped from [-1, 1] to [0, 1]. Same vertex
+// input as the Lambert shader, so it can replace it on a drawable, see Drawable::replace_shaders
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) normal: vec3<f32>,
+};
+
+struct FragmentInput {
+    @location(0) normal: vec3<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct TransformUniform {
+    m: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: TransformUniform;
+
+@group(1) @binding(0)
+var<uniform> transform: TransformUniform;
+
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    // World space normal, valid as long as the transform only has uniform scaling
+    out.normal = (transform.m * vec4<f32>(vtx_in.normal, 0.0)).xyz;
+    out.position = camera.m * transform.m * vec4<f32>(vtx_in.position, 1.0);
+    return out;
+}
+
+@fragment
+fn frg_main(
+    frg_in: FragmentInput,
+    @builtin(front_facing) front_facing: bool,
+) -> @location(0) vec4<f32> {
+    let normal = select(-1.0, 1.0, front_facing) * normalize(frg_in.normal);
+    return vec4<f32>(normal * 0.5 + 0.5, 1.0);
+}
@@ -0,0 +1,35 @@
+// Prepended to the post-process code, which must define:
+// fn post_process(uv: vec2<f32>) -> vec4<f32>
+// The rendered scene is sampled with source_texture and source_sampler, uv being (0, 0) at the
+// top-left corner.
+
+struct FragmentInput {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+// Free to use by the post-process, see PostProcess::set_params
+@group(1) @binding(0)
+var<uniform> params: vec4<f32>;
+
+
+// Fullscreen triangle generated from the vertex index, no vertex buffer needed
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex_index: u32) -> FragmentInput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: FragmentInput;
+    out.uv = uv;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    return post_process(frg_in.uv);
+}
+
@@ -0,0 +1,21 @@
+// Scene-wide uniforms, bound by the draw context at group 2 for every drawable, see
+// DrawContext::BIND_GROUP_INDEX_SCENE. Prepended to the shaders using them.
+
+struct LightUniform {
+    direction: vec3<f32>,
+    ambient: f32,
+    color: vec3<f32>,
+};
+@group(2) @binding(0)
+var<uniform> light: LightUniform;
+
+// Time in seconds, resolution and mouse in pixels, like the shadertoy iTime, iResolution and
+// iMouse uniforms
+struct GlobalsUniform {
+    time: f32,
+    resolution: vec2<f32>,
+    mouse: vec4<f32>,
+};
+@group(2) @binding(1)
+var<uniform> globals: GlobalsUniform;
+
@@ -0,0 +1,37 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+};
+
+struct FragmentInput {
+    @location(0) direction: vec3<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+// Bound in place of the transform, the camera matrix has no translation
+struct SkyboxUniform {
+    m: mat4x4<f32>,
+    far_depth: f32,
+};
+@group(1) @binding(0)
+var<uniform> skybox: SkyboxUniform;
+
+@group(3) @binding(0)
+var skybox_texture: texture_cube<f32>;
+@group(3) @binding(1)
+var skybox_sampler: sampler;
+
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    out.direction = vtx_in.position;
+    let position = skybox.m * vec4<f32>(vtx_in.position, 1.0);
+    // Pushed on the far plane, so that it stays behind everything else
+    out.position = vec4<f32>(position.xy, skybox.far_depth * position.w, position.w);
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    return textureSample(skybox_texture, skybox_sampler, frg_in.direction);
+}
@@ -0,0 +1,42 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+struct FragmentInput {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct CameraUniform {
+    m: mat4x4<f32>,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct TransformUniform {
+    m: mat4x4<f32>,
+    opacity: f32,
+};
+@group(1) @binding(0)
+var<uniform> transform: TransformUniform;
+
+@group(3) @binding(0)
+var diffuse_texture: texture_2d<f32>;
+@group(3) @binding(1)
+var diffuse_sampler: sampler;
+
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    out.uv = vtx_in.uv;
+    out.position = camera.m * transform.m * vec4<f32>(vtx_in.position, 1.0);
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let color = textureSample(diffuse_texture, diffuse_sampler, frg_in.uv);
+    return vec4<f32>(color.rgb, color.a * transform.opacity);
+}
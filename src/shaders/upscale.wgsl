@@ -0,0 +1,6 @@
+// Post-process used to stretch a scene rendered with a render scale to the surface, the bilinear
+// filtering is done by the source sampler, see DrawContext::set_render_scale
+
+fn post_process(uv: vec2<f32>) -> vec4<f32> {
+    return textureSample(source_texture, source_sampler, uv);
+}
@@ -0,0 +1,185 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use glyphon::{
+    Attrs, Buffer, Cache, Color, Family, FontSystem, Metrics, Resolution, Shaping, SwashCache,
+    TextArea, TextAtlas, TextBounds, TextRenderer, Viewport,
+};
+use log::warn;
+
+use crate::draw_context::{Dimensions, DrawContext, RenderOverlay};
+
+const LINE_HEIGHT_FACTOR: f32 = 1.2;
+
+struct TextEntry {
+    buffer: Buffer,
+    position: [f32; 2],
+    color: Color,
+}
+
+// Screen space text, drawn over the scene with DrawContext::render_scene_with_overlay.
+// Positions are in physical pixels from the top-left corner, sizes in pixels too.
+pub struct TextLayer {
+    font_system: FontSystem,
+    swash_cache: SwashCache,
+    viewport: Viewport,
+    atlas: TextAtlas,
+    renderer: TextRenderer,
+    entries: Vec<TextEntry>,
+}
+
+impl TextLayer {
+    pub fn new(context: &DrawContext) -> Self {
+        let cache = Cache::new(&context.device);
+        let viewport = Viewport::new(&context.device, &cache);
+        let mut atlas = TextAtlas::new(
+            &context.device,
+            &context.queue,
+            &cache,
+            context.surface_format(),
+        );
+        let renderer = TextRenderer::new(
+            &mut atlas,
+            &context.device,
+            wgpu::MultisampleState::default(),
+            None,
+        );
+        TextLayer {
+            font_system: FontSystem::new(),
+            swash_cache: SwashCache::new(),
+            viewport,
+            atlas,
+            renderer,
+            entries: Vec::new(),
+        }
+    }
+
+    // Returns the index of the text, to be used with set_text
+    pub fn add_text(
+        &mut self,
+        content: &str,
+        position: [f32; 2],
+        size: f32,
+        color: [f32; 4],
+    ) -> usize {
+        let mut buffer = Buffer::new(
+            &mut self.font_system,
+            Metrics::new(size, size * LINE_HEIGHT_FACTOR),
+        );
+        buffer.set_size(&mut self.font_system, None, None);
+        self.entries.push(TextEntry {
+            buffer,
+            position,
+            color: Self::to_glyphon_color(color),
+        });
+        let index = self.entries.len() - 1;
+        self.set_text(index, content);
+        index
+    }
+
+    pub fn set_text(&mut self, index: usize, content: &str) {
+        let Some(entry) = self.entries.get_mut(index) else {
+            return;
+        };
+        entry.buffer.set_text(
+            &mut self.font_system,
+            content,
+            Attrs::new().family(Family::SansSerif),
+            Shaping::Advanced,
+        );
+        entry
+            .buffer
+            .shape_until_scroll(&mut self.font_system, false);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn to_glyphon_color(color: [f32; 4]) -> Color {
+        let [r, g, b, a] = color.map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8);
+        Color::rgba(r, g, b, a)
+    }
+}
+
+impl RenderOverlay for TextLayer {
+    fn render_overlay(
+        &mut self,
+        context: &DrawContext,
+        encoder: &mut wgpu::CommandEncoder,
+        target_view: &wgpu::TextureView,
+    ) {
+        let Dimensions { width, height } = context.size();
+        self.viewport
+            .update(&context.queue, Resolution { width, height });
+        let text_areas = self.entries.iter().map(|entry| TextArea {
+            buffer: &entry.buffer,
+            left: entry.position[0],
+            top: entry.position[1],
+            scale: 1.0,
+            bounds: TextBounds {
+                left: 0,
+                top: 0,
+                right: width as i32,
+                bottom: height as i32,
+            },
+            default_color: entry.color,
+            custom_glyphs: &[],
+        });
+        if let Err(err) = self.renderer.prepare(
+            &context.device,
+            &context.queue,
+            &mut self.font_system,
+            &mut self.atlas,
+            &self.viewport,
+            text_areas,
+            &mut self.swash_cache,
+        ) {
+            warn!("Could not prepare text: {err}");
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Text"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Err(err) = self
+            .renderer
+            .render(&self.atlas, &self.viewport, &mut render_pass)
+        {
+            warn!("Could not render text: {err}");
+        }
+        drop(render_pass);
+        self.atlas.trim();
+    }
+}
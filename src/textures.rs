@@ -0,0 +1,439 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::rc::Rc;
+
+use anyhow::{bail, Context};
+use log::warn;
+
+use crate::draw_context::DrawContext;
+
+pub const TEXTURE_SHADER: &str = include_str!("shaders/texture.wgsl");
+const BLIT_SHADER: &str = include_str!("shaders/blit.wgsl");
+
+// Number of levels down to 1x1, also valid for non power of two dimensions
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+#[derive(Clone, Debug)]
+pub struct SamplerConfig {
+    // 1 disables anisotropic filtering, values are clamped to what the device supports
+    pub anisotropy_clamp: u16,
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        SamplerConfig {
+            anisotropy_clamp: 1,
+        }
+    }
+}
+
+// Validated RGBA data and sampling options of a Texture2D
+pub(crate) struct Texture2DDescriptor<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: &'a [u8],
+    pub with_mips: bool,
+    pub anisotropy_clamp: u16,
+}
+
+// Texture bound at DrawContext::BIND_GROUP_INDEX_TEXTURE, with its sampler
+pub struct Texture2D {
+    texture: wgpu::Texture,
+    bind_group: Rc<wgpu::BindGroup>,
+}
+
+impl Texture2D {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+    const BYTES_PER_PIXEL: u32 = 4;
+
+    pub fn from_bytes(context: &DrawContext, bytes: &[u8]) -> anyhow::Result<Self> {
+        Self::from_bytes_with_config(context, bytes, false, &SamplerConfig::default())
+    }
+
+    // Mipmaps avoid shimmering when the texture is seen from a distance
+    pub fn from_bytes_with_mips(context: &DrawContext, bytes: &[u8]) -> anyhow::Result<Self> {
+        Self::from_bytes_with_config(context, bytes, true, &SamplerConfig::default())
+    }
+
+    pub fn from_bytes_with_config(
+        context: &DrawContext,
+        bytes: &[u8],
+        with_mips: bool,
+        sampler_config: &SamplerConfig,
+    ) -> anyhow::Result<Self> {
+        let image = image::load_from_memory(bytes)
+            .context("Failed to decode texture image")?
+            .to_rgba8();
+        Self::from_rgba(
+            context,
+            image.width(),
+            image.height(),
+            &image,
+            with_mips,
+            sampler_config,
+        )
+    }
+
+    pub fn from_rgba(
+        context: &DrawContext,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+        with_mips: bool,
+        sampler_config: &SamplerConfig,
+    ) -> anyhow::Result<Self> {
+        if width == 0 || height == 0 {
+            bail!("Texture dimensions must not be zero");
+        }
+        if rgba.len() != (width * height * Self::BYTES_PER_PIXEL) as usize {
+            bail!("Texture data does not match a {width}x{height} RGBA image");
+        }
+        let max_anisotropy_clamp = context.max_anisotropy_clamp();
+        let anisotropy_clamp = if sampler_config.anisotropy_clamp > max_anisotropy_clamp {
+            warn!(
+                "Anisotropy clamp {} not supported, using {max_anisotropy_clamp}",
+                sampler_config.anisotropy_clamp
+            );
+            max_anisotropy_clamp
+        } else {
+            sampler_config.anisotropy_clamp.max(1)
+        };
+        Ok(Self::create(
+            &context.device,
+            &context.queue,
+            &context.texture_bind_group_layout,
+            &Texture2DDescriptor {
+                width,
+                height,
+                rgba,
+                with_mips,
+                anisotropy_clamp,
+            },
+        ))
+    }
+
+    pub(crate) fn create(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        descriptor: &Texture2DDescriptor,
+    ) -> Self {
+        let &Texture2DDescriptor {
+            width,
+            height,
+            rgba,
+            with_mips,
+            anisotropy_clamp,
+        } = descriptor;
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let mip_level_count = if with_mips {
+            mip_level_count(width, height)
+        } else {
+            1
+        };
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        if mip_level_count > 1 {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Texture 2D"),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            texture.as_image_copy(),
+            rgba,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * Self::BYTES_PER_PIXEL),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        if mip_level_count > 1 {
+            generate_mipmaps(device, queue, &texture);
+        }
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Texture 2D Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            anisotropy_clamp,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Texture 2D bind group"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        Texture2D {
+            texture,
+            bind_group: Rc::new(bind_group),
+        }
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn mip_level_count(&self) -> u32 {
+        self.texture.mip_level_count()
+    }
+
+    pub(crate) fn bind_group(&self) -> &Rc<wgpu::BindGroup> {
+        &self.bind_group
+    }
+}
+
+// Six square faces sampled with a direction, in the order +X, -X, +Y, -Y, +Z, -Z. Not usable
+// with the drawables, the bind group has its own layout, see CubeTexture::bind_group_layout
+pub struct CubeTexture {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+impl CubeTexture {
+    const FACE_COUNT: usize = 6;
+
+    pub fn from_bytes(context: &DrawContext, faces: [&[u8]; 6]) -> anyhow::Result<Self> {
+        let mut images = Vec::with_capacity(Self::FACE_COUNT);
+        for (index, bytes) in faces.into_iter().enumerate() {
+            let image = image::load_from_memory(bytes)
+                .with_context(|| format!("Failed to decode cube face {index}"))?
+                .to_rgba8();
+            images.push(image);
+        }
+        let size = images[0].width();
+        if images
+            .iter()
+            .any(|image| image.width() != size || image.height() != size)
+        {
+            bail!("Cube texture faces must be squares of the same size");
+        }
+        let rgba: Vec<u8> = images
+            .iter()
+            .flat_map(|image| image.as_raw())
+            .copied()
+            .collect();
+        Self::from_rgba(context, size, &rgba)
+    }
+
+    // Faces are stored one after the other, each of size x size RGBA pixels
+    pub fn from_rgba(context: &DrawContext, size: u32, rgba: &[u8]) -> anyhow::Result<Self> {
+        if size == 0 {
+            bail!("Cube texture dimensions must not be zero");
+        }
+        let face_len = (size * size * Texture2D::BYTES_PER_PIXEL) as usize;
+        if rgba.len() != face_len * Self::FACE_COUNT {
+            bail!("Cube texture data does not match six {size}x{size} RGBA faces");
+        }
+        let device = &context.device;
+        let texture_size = wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: Self::FACE_COUNT as u32,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Cube Texture"),
+            size: texture_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Texture2D::FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        context.queue.write_texture(
+            texture.as_image_copy(),
+            rgba,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(size * Texture2D::BYTES_PER_PIXEL),
+                rows_per_image: Some(size),
+            },
+            texture_size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Cube Texture Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Cube Texture bind group"),
+            layout: &Self::bind_group_layout(context),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        Ok(CubeTexture {
+            texture,
+            bind_group,
+        })
+    }
+
+    pub fn bind_group_layout(context: &DrawContext) -> Rc<wgpu::BindGroupLayout> {
+        context.get_bind_group_layout(&[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ])
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub(crate) fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+// Each level is rendered by sampling the previous one with a linear filter
+fn generate_mipmaps(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Mipmap Blit Shader"),
+        source: wgpu::ShaderSource::Wgsl(BLIT_SHADER.into()),
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Mipmap Blit Pipeline"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: None,
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: None,
+            compilation_options: Default::default(),
+            targets: &[Some(texture.format().into())],
+        }),
+        primitive: Default::default(),
+        depth_stencil: None,
+        multisample: Default::default(),
+        multiview: None,
+        cache: None,
+    });
+    let bind_group_layout = pipeline.get_bind_group_layout(0);
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Mipmap Blit Sampler"),
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+    let level_view = |level| {
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            base_mip_level: level,
+            mip_level_count: Some(1),
+            ..Default::default()
+        })
+    };
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Mipmap Encoder"),
+    });
+    for level in 1..texture.mip_level_count() {
+        let source_view = level_view(level - 1);
+        let target_view = level_view(level);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Mipmap Blit bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Mipmap Blit Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+}
@@ -0,0 +1,321 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use log::{debug, warn};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use web_time::{Duration, Instant};
+
+use crate::window::UpdateMode;
+
+// Source of the time used for the updates, so that they can be made deterministic
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// Only moves forward when advanced, e.g. by a fixed step before each update. Can be shared
+// through an Rc, to keep advancing it after giving it to the app
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Cell<Instant>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock {
+            now: Cell::new(Instant::now()),
+        }
+    }
+
+    pub fn advance(&self, delta: Duration) {
+        self.now.set(self.now.get() + delta);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+// One scenario update to run, see UpdateTimer::tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpdateStep {
+    pub update_delta: Duration,
+    // Scaled time since the scenario start, including this update
+    pub scenario_elapsed: Duration,
+}
+
+// Turns the time read from a clock into scenario updates, following the time control and the
+// update mode. Used by the app on each frame, and usable with a ManualClock to replay updates.
+pub struct UpdateTimer {
+    clock: Rc<dyn Clock>,
+    scenario_start: Instant,
+    last_update_instant: Instant,
+    update_accumulator: Duration,
+    scenario_elapsed: Duration,
+}
+
+impl UpdateTimer {
+    pub fn new(clock: Rc<dyn Clock>) -> Self {
+        let scenario_start = clock.now();
+        UpdateTimer {
+            clock,
+            scenario_start,
+            last_update_instant: scenario_start,
+            update_accumulator: Duration::ZERO,
+            scenario_elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn scenario_start(&self) -> Instant {
+        self.scenario_start
+    }
+
+    pub fn scenario_elapsed(&self) -> Duration {
+        self.scenario_elapsed
+    }
+
+    // So the time spent since the last tick is not seen as one long update, e.g. after the window
+    // was minimized
+    pub fn skip_elapsed(&mut self) {
+        self.last_update_instant = self.clock.now();
+    }
+
+    // Updates to run since the last tick. With UpdateMode::Fixed, the time left over is kept for
+    // the next tick, and the late updates beyond max_steps are dropped.
+    pub fn tick(
+        &mut self,
+        time_control: &TimeControl,
+        update_mode: &UpdateMode,
+    ) -> Vec<UpdateStep> {
+        let now = self.clock.now();
+        let elapsed = now.saturating_duration_since(self.last_update_instant);
+        self.last_update_instant = now;
+        let elapsed = time_control.scale_delta(elapsed);
+        match update_mode {
+            UpdateMode::Variable => {
+                self.scenario_elapsed += elapsed;
+                vec![UpdateStep {
+                    update_delta: elapsed,
+                    scenario_elapsed: self.scenario_elapsed,
+                }]
+            }
+            &UpdateMode::Fixed { delta, max_steps } => {
+                self.update_accumulator += elapsed;
+                let mut steps = Vec::new();
+                while self.update_accumulator >= delta && steps.len() < max_steps as usize {
+                    self.scenario_elapsed += delta;
+                    steps.push(UpdateStep {
+                        update_delta: delta,
+                        scenario_elapsed: self.scenario_elapsed,
+                    });
+                    self.update_accumulator -= delta;
+                }
+                if self.update_accumulator >= delta {
+                    // Frame stalled for too long, drop the backlog instead of trying to catch up
+                    debug!("Too many fixed update steps, dropping late updates");
+                    self.update_accumulator = Duration::from_nanos(
+                        (self.update_accumulator.as_nanos() % delta.as_nanos()) as u64,
+                    );
+                }
+                steps
+            }
+        }
+    }
+}
+
+pub struct TimeControl {
+    paused: bool,
+    time_scale: f32,
+}
+
+impl TimeControl {
+    const TIME_SCALE_STEP: f32 = 2.0;
+    const MIN_TIME_SCALE: f32 = 1.0 / 64.0;
+    const MAX_TIME_SCALE: f32 = 64.0;
+
+    pub fn new() -> Self {
+        TimeControl {
+            paused: false,
+            time_scale: 1.0,
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Non-finite values are ignored, as Duration::mul_f32 would panic with them
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        if !time_scale.is_finite() {
+            warn!("Ignoring non-finite time scale {time_scale}");
+            return;
+        }
+        self.time_scale = time_scale.max(0.);
+    }
+
+    pub fn get_time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn scale_delta(&self, delta: Duration) -> Duration {
+        if self.paused {
+            return Duration::ZERO;
+        }
+        delta.mul_f32(self.time_scale)
+    }
+
+    pub fn slow_down(&mut self) {
+        self.set_time_scale((self.time_scale / Self::TIME_SCALE_STEP).max(Self::MIN_TIME_SCALE));
+    }
+
+    pub fn speed_up(&mut self) {
+        self.set_time_scale((self.time_scale * Self::TIME_SCALE_STEP).min(Self::MAX_TIME_SCALE));
+    }
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct FrameCounter {
+    frame_deltas: VecDeque<Duration>,
+    frame_deltas_sum: Duration,
+    capacity: usize,
+}
+
+impl FrameCounter {
+    const DEFAULT_CAPACITY: usize = 60;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        FrameCounter {
+            frame_deltas: VecDeque::with_capacity(capacity),
+            frame_deltas_sum: Duration::ZERO,
+            capacity,
+        }
+    }
+
+    pub fn push_frame(&mut self, frame_delta: Duration) {
+        if self.frame_deltas.len() == self.capacity {
+            if let Some(oldest) = self.frame_deltas.pop_front() {
+                self.frame_deltas_sum -= oldest;
+            }
+        }
+        self.frame_deltas.push_back(frame_delta);
+        self.frame_deltas_sum += frame_delta;
+    }
+
+    pub fn last_frame_time(&self) -> Duration {
+        self.frame_deltas.back().copied().unwrap_or_default()
+    }
+
+    pub fn average_frame_time(&self) -> Duration {
+        if self.frame_deltas.is_empty() {
+            return Duration::ZERO;
+        }
+        self.frame_deltas_sum / self.frame_deltas.len() as u32
+    }
+
+    pub fn instant_fps(&self) -> f32 {
+        Self::to_fps(self.last_frame_time())
+    }
+
+    pub fn average_fps(&self) -> f32 {
+        Self::to_fps(self.average_frame_time())
+    }
+
+    fn to_fps(frame_time: Duration) -> f32 {
+        if frame_time.is_zero() {
+            return 0.;
+        }
+        1. / frame_time.as_secs_f32()
+    }
+}
+
+impl Default for FrameCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_updates_keep_the_remainder_and_follow_the_time_scale() {
+        let clock = Rc::new(ManualClock::new());
+        let mut update_timer = UpdateTimer::new(clock.clone());
+        let mut time_control = TimeControl::new();
+        let update_mode = UpdateMode::fixed(Duration::from_millis(10));
+        clock.advance(Duration::from_millis(25));
+        let steps = update_timer.tick(&time_control, &update_mode);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[1].scenario_elapsed, Duration::from_millis(20));
+        time_control.set_time_scale(2.0);
+        clock.advance(Duration::from_millis(5));
+        let steps = update_timer.tick(&time_control, &update_mode);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(update_timer.scenario_elapsed(), Duration::from_millis(30));
+        time_control.pause();
+        clock.advance(Duration::from_secs(1));
+        assert!(update_timer.tick(&time_control, &update_mode).is_empty());
+    }
+}
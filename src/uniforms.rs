@@ -0,0 +1,224 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use bytemuck::Pod;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::draw_context::DrawContext;
+
+// Layout entry of a uniform of type T, for DrawContext::get_bind_group_layout. Visible to the
+// vertex and fragment stages when no visibility is given, restricting it to the stages using
+// the uniform spares some validation, and is required for compute-only bindings.
+pub fn uniform_layout_entry<T: Pod>(
+    binding: u32,
+    visibility: Option<wgpu::ShaderStages>,
+) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: visibility.unwrap_or(wgpu::ShaderStages::VERTEX_FRAGMENT),
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: wgpu::BufferSize::new(size_of::<T>() as wgpu::BufferAddress),
+        },
+        count: None,
+    }
+}
+
+// Buffer holding a single uniform value, to bind in user bind groups.
+//
+// Created with new_dynamic, the uniform rotates between several buffers, each write targeting
+// the next one. This helps when the value is rewritten every frame and the backend waits for the
+// GPU to be done with a buffer before updating it again (seen with OpenGL and some mobile
+// drivers). On Vulkan, Metal and DX12, write_buffer is staged and the simple path is enough.
+// As the current buffer changes, a bind group must be created for each buffer, see
+// binding_resource_at and current_index.
+pub struct Uniform<T: Pod> {
+    buffers: Vec<wgpu::Buffer>,
+    current: Cell<usize>,
+    _value: PhantomData<T>,
+}
+
+// Uniform referenced by several owners, e.g. drawables or scenes created at different times,
+// any of them being able to write it
+pub type SharedUniform<T> = Rc<Uniform<T>>;
+
+impl<T: Pod> Uniform<T> {
+    pub fn new_shared(context: &DrawContext, label: &str, value: &T) -> SharedUniform<T> {
+        Rc::new(Self::new(context, label, value))
+    }
+
+    pub fn new(context: &DrawContext, label: &str, value: &T) -> Self {
+        Self::new_dynamic(context, label, value, 1)
+    }
+
+    pub fn new_dynamic(context: &DrawContext, label: &str, value: &T, count: usize) -> Self {
+        assert!(count > 0, "A uniform needs at least one buffer");
+        let buffers = (0..count)
+            .map(|_| {
+                context.device.create_buffer_init(&BufferInitDescriptor {
+                    label: Some(label),
+                    contents: bytemuck::bytes_of(value),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                })
+            })
+            .collect();
+        Uniform {
+            buffers,
+            current: Cell::new(0),
+            _value: PhantomData,
+        }
+    }
+
+    pub fn write_uniform(&self, context: &DrawContext, value: &T) {
+        let current = (self.current.get() + 1) % self.buffers.len();
+        self.current.set(current);
+        context
+            .queue
+            .write_buffer(&self.buffers[current], 0, bytemuck::bytes_of(value));
+    }
+
+    // Buffer holding the last written value
+    pub fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        self.binding_resource_at(self.current.get())
+    }
+
+    pub fn binding_resource_at(&self, index: usize) -> wgpu::BindingResource<'_> {
+        self.buffers[index].as_entire_binding()
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current.get()
+    }
+
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn layout_entry(
+        binding: u32,
+        visibility: Option<wgpu::ShaderStages>,
+    ) -> wgpu::BindGroupLayoutEntry {
+        uniform_layout_entry::<T>(binding, visibility)
+    }
+}
+
+// Offset of a field in a uniform struct, to use with UniformBuffer::write_field
+#[macro_export]
+macro_rules! uniform_offset {
+    ($uniform:ty, $field:ident) => {
+        ::std::mem::offset_of!($uniform, $field) as ::wgpu::BufferAddress
+    };
+}
+
+// Uniform buffer allowing to update part of the value, e.g. a single field of a large struct,
+// instead of uploading it entirely
+pub struct UniformBuffer<T: Pod> {
+    buffer: wgpu::Buffer,
+    _value: PhantomData<T>,
+}
+
+impl<T: Pod> UniformBuffer<T> {
+    pub fn new(context: &DrawContext, label: &str, value: &T) -> Self {
+        let buffer = context.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::bytes_of(value),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        UniformBuffer {
+            buffer,
+            _value: PhantomData,
+        }
+    }
+
+    pub fn write(&self, context: &DrawContext, value: &T) {
+        context
+            .queue
+            .write_buffer(&self.buffer, 0, bytemuck::bytes_of(value));
+    }
+
+    // The offset is usually given by uniform_offset!, offset and length must be multiples of 4
+    pub fn write_field(&self, context: &DrawContext, offset: wgpu::BufferAddress, bytes: &[u8]) {
+        let end = offset + bytes.len() as wgpu::BufferAddress;
+        assert!(
+            end <= size_of::<T>() as wgpu::BufferAddress,
+            "Write of {} bytes at offset {offset} is out of the uniform",
+            bytes.len()
+        );
+        assert!(
+            offset.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+                && (bytes.len() as wgpu::BufferAddress).is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT),
+            "Uniform field writes must be aligned to {} bytes",
+            wgpu::COPY_BUFFER_ALIGNMENT
+        );
+        context.queue.write_buffer(&self.buffer, offset, bytes);
+    }
+
+    pub fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        self.buffer.as_entire_binding()
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn layout_entry(
+        binding: u32,
+        visibility: Option<wgpu::ShaderStages>,
+    ) -> wgpu::BindGroupLayoutEntry {
+        uniform_layout_entry::<T>(binding, visibility)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniform_layout_entry_binds_at_the_given_binding() {
+        let entries = [
+            Uniform::<[f32; 4]>::layout_entry(0, None),
+            Uniform::<[f32; 4]>::layout_entry(1, Some(wgpu::ShaderStages::FRAGMENT)),
+        ];
+        assert_eq!(entries[0].binding, 0);
+        assert_eq!(entries[0].visibility, wgpu::ShaderStages::VERTEX_FRAGMENT);
+        assert_eq!(entries[1].binding, 1);
+        assert_eq!(entries[1].visibility, wgpu::ShaderStages::FRAGMENT);
+        for entry in entries {
+            assert_eq!(entry.count, None);
+            assert_eq!(
+                entry.ty,
+                wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(16),
+                }
+            );
+        }
+    }
+}
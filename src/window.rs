@@ -22,28 +22,149 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use web_time::{Duration, Instant};
 
 use winit::application::ApplicationHandler;
-use winit::event::{DeviceEvent, ElementState, MouseButton, WindowEvent};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceEvent, ElementState, MouseButton, Touch, TouchPhase, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
-use winit::window::{CursorIcon, Window, WindowId};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{CursorIcon, Fullscreen, Window, WindowId};
 
 use crate::cameras::{Camera, PerspectiveConfig, WinitCameraAdapter};
-use crate::draw_context::{self, Dimensions, DrawContext};
-use crate::scenario::{Scenario, UpdateInterval};
-use log::debug;
+use crate::canvas::CanvasUniforms;
+use crate::draw_context::{self, Dimensions, DrawContext, DrawContextConfig};
+#[cfg(feature = "egui")]
+use crate::gui::EguiOverlay;
+use crate::input::InputState;
+use crate::scenario::{CursorPosition, RenderStats, Scenario, UpdateInterval};
+use crate::timing::{Clock, FrameCounter, SystemClock, TimeControl, UpdateStep, UpdateTimer};
+#[cfg(feature = "gamepad")]
+use log::warn;
+use log::{debug, error, info};
 
 #[cfg(target_arch = "wasm32")]
 const WEBAPP_CANVAS_ID: &str = "target";
 
-const TARGET_DRAW_FPS: f64 = 60.0;
+const DEFAULT_TARGET_DRAW_FPS: f64 = 60.0;
+const DEFAULT_MAX_FIXED_STEPS: u32 = 8;
+
+#[derive(Default)]
+pub enum UpdateMode {
+    #[default]
+    Variable,
+    Fixed {
+        delta: Duration,
+        max_steps: u32,
+    },
+}
+
+impl UpdateMode {
+    pub fn fixed(delta: Duration) -> Self {
+        assert!(!delta.is_zero(), "Fixed update delta must not be zero");
+        UpdateMode::Fixed {
+            delta,
+            max_steps: DEFAULT_MAX_FIXED_STEPS,
+        }
+    }
+}
+
+// With OnDemand, the event loop sleeps until an input event arrives, a key is held or the
+// scenario needs a redraw, see DynScenario::needs_redraw. Better suited to static scenes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RedrawMode {
+    #[default]
+    Continuous,
+    OnDemand,
+}
+
+// Only applied on desktop, in the browser the window is the canvas, sized after the page
+#[derive(Clone, Debug)]
+pub struct WindowConfig {
+    // The winit default title is kept when None
+    pub title: Option<String>,
+    // Initial inner size in logical pixels, the platform default being used when None
+    pub size: Option<Dimensions>,
+    pub resizable: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: None,
+            size: None,
+            resizable: true,
+        }
+    }
+}
+
+impl WindowConfig {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn window_attributes(&self) -> winit::window::WindowAttributes {
+        let mut window_attributes = Window::default_attributes().with_resizable(self.resizable);
+        if let Some(ref title) = self.title {
+            window_attributes = window_attributes.with_title(title);
+        }
+        if let Some(size) = self.size {
+            window_attributes = window_attributes
+                .with_inner_size(winit::dpi::LogicalSize::new(size.width, size.height));
+        }
+        window_attributes
+    }
+}
+
+pub struct AppConfig {
+    pub update_mode: UpdateMode,
+    pub redraw_mode: RedrawMode,
+    // None or a non-positive value means uncapped, a redraw is always requested. Ignored with
+    // a present mode without vsync, see DrawContextConfig::present_mode
+    pub target_fps: Option<f64>,
+    // None disables the fullscreen toggle
+    pub fullscreen_key: Option<KeyCode>,
+    // None disables screenshots
+    pub screenshot_key: Option<KeyCode>,
+    // None disables the perspective and orthogonal projection toggle
+    pub projection_key: Option<KeyCode>,
+    // None disables pausing the updates, see TimeControl
+    pub pause_key: Option<KeyCode>,
+    // None disables halving the time scale
+    pub slow_down_key: Option<KeyCode>,
+    // None disables doubling the time scale
+    pub speed_up_key: Option<KeyCode>,
+    pub draw_context_config: DrawContextConfig,
+    pub window_config: WindowConfig,
+    // Time source of the updates, a ManualClock makes them deterministic
+    pub clock: Rc<dyn Clock>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            update_mode: Default::default(),
+            redraw_mode: Default::default(),
+            target_fps: Some(DEFAULT_TARGET_DRAW_FPS),
+            fullscreen_key: Some(KeyCode::F11),
+            screenshot_key: Some(KeyCode::KeyP),
+            projection_key: Some(KeyCode::KeyO),
+            pause_key: Some(KeyCode::Space),
+            slow_down_key: Some(KeyCode::Minus),
+            speed_up_key: Some(KeyCode::Equal),
+            draw_context_config: DrawContextConfig::from_env(),
+            window_config: WindowConfig::default(),
+            clock: Rc::new(SystemClock),
+        }
+    }
+}
 
 struct MouseState {
     pub is_cursor_inside: bool,
     mouse_rotation_enabled: bool,
+    cursor_position: Option<PhysicalPosition<f64>>,
+    last_click_position: Option<PhysicalPosition<f64>>,
 }
 
 impl MouseState {
@@ -51,6 +172,8 @@ impl MouseState {
         MouseState {
             is_cursor_inside: false,
             mouse_rotation_enabled: false,
+            cursor_position: None,
+            last_click_position: None,
         }
     }
     pub fn left_button_action(&mut self, action: ElementState, window: &Window) {
@@ -60,6 +183,7 @@ impl MouseState {
         match action {
             ElementState::Pressed => {
                 self.mouse_rotation_enabled = true;
+                self.last_click_position = self.cursor_position;
                 // FIXME disabled due to winit error when resizing in web context: already borrowed: BorrowMutError on window.set_cursor
                 #[cfg(not(target_arch = "wasm32"))]
                 window.set_cursor_visible(false);
@@ -87,56 +211,265 @@ impl MouseState {
     pub fn move_action(&mut self) {
         self.mouse_rotation_enabled = false;
     }
+
+    pub fn cursor_moved_action(&mut self, position: PhysicalPosition<f64>) {
+        self.cursor_position = Some(position);
+    }
+
+    pub fn cursor_left_action(&mut self) {
+        self.is_cursor_inside = false;
+        self.cursor_position = None;
+    }
+}
+
+// Tracks active touches, by id, to rotate the camera with one finger and zoom with two fingers
+struct TouchState {
+    touches: HashMap<u64, PhysicalPosition<f64>>,
+    pinch_distance: Option<f64>,
+}
+
+impl TouchState {
+    // Screen pixels of pinch distance change needed to move as much as one arrow key step
+    const PINCH_ZOOM_FACTOR: f32 = 1.0 / 4.0;
+
+    fn new() -> Self {
+        TouchState {
+            touches: HashMap::new(),
+            pinch_distance: None,
+        }
+    }
+
+    fn touch_action(&mut self, touch: &Touch, camera: &mut WinitCameraAdapter) {
+        match touch.phase {
+            TouchPhase::Started => {
+                self.touches.insert(touch.id, touch.location);
+                self.pinch_distance = self.current_pinch_distance();
+            }
+            TouchPhase::Moved => {
+                let Some(previous) = self.touches.insert(touch.id, touch.location) else {
+                    return;
+                };
+                match self.touches.len() {
+                    1 => camera.rotate(
+                        (touch.location.x - previous.x) as f32,
+                        (touch.location.y - previous.y) as f32,
+                    ),
+                    2 => {
+                        let distance = self.current_pinch_distance();
+                        if let (Some(previous), Some(current)) = (self.pinch_distance, distance) {
+                            camera.zoom((current - previous) as f32 * Self::PINCH_ZOOM_FACTOR);
+                        }
+                        self.pinch_distance = distance;
+                    }
+                    _ => {}
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&touch.id);
+                self.pinch_distance = self.current_pinch_distance();
+            }
+        }
+    }
+
+    fn current_pinch_distance(&self) -> Option<f64> {
+        if self.touches.len() != 2 {
+            return None;
+        }
+        let mut positions = self.touches.values();
+        let (first, second) = (positions.next()?, positions.next()?);
+        Some((first.x - second.x).hypot(first.y - second.y))
+    }
+}
+
+// The camera provided by the scenario, or the one of the app
+fn active_camera<'a, S: Scenario>(
+    scenario: &'a mut S,
+    app_camera: &'a mut WinitCameraAdapter,
+) -> &'a mut WinitCameraAdapter {
+    scenario.active_camera().unwrap_or(app_camera)
 }
 
 struct App<S> {
     window: Arc<Window>,
     mouse_state: MouseState,
-    scenario_start: Instant,
-    last_draw_instant: Instant,
-    draw_period_target: Duration,
+    input_state: InputState,
+    touch_state: TouchState,
+    update_timer: UpdateTimer,
+    // Instant of the last frame, from the wall clock, for the frame pacing and counter. Kept apart
+    // from the update clock, so a ManualClock does not freeze the window.
+    last_frame_instant: Instant,
+    draw_period_target: Option<Duration>,
+    fullscreen_key: Option<KeyCode>,
+    screenshot_key: Option<KeyCode>,
+    projection_key: Option<KeyCode>,
+    pause_key: Option<KeyCode>,
+    slow_down_key: Option<KeyCode>,
+    speed_up_key: Option<KeyCode>,
+    update_mode: UpdateMode,
+    redraw_mode: RedrawMode,
+    // Set by input events, for RedrawMode::OnDemand
+    redraw_pending: bool,
+    time_control: TimeControl,
+    frame_counter: FrameCounter,
+    last_render_stats: RenderStats,
+    // Set on a zero size resize, nothing is rendered until the window has a size again
+    minimized: bool,
     winit_camera: WinitCameraAdapter,
+    #[cfg(feature = "gamepad")]
+    gamepad: Option<gilrs::Gilrs>,
     draw_context: DrawContext,
+    #[cfg(feature = "egui")]
+    gui: EguiOverlay,
     scenario: S,
 }
 
 impl<S: Scenario> App<S> {
-    async fn async_new(window: Window, dimensions: Option<Dimensions>) -> Self {
+    async fn async_new(
+        window: Window,
+        dimensions: Option<Dimensions>,
+        config: AppConfig,
+    ) -> anyhow::Result<Self> {
         let window = Arc::new(window);
         let mouse_state = MouseState::new();
-        let scenario_start = Instant::now();
-        let last_draw_instant = scenario_start;
-        let draw_period_target = Duration::from_secs_f64(1.0 / TARGET_DRAW_FPS);
+        let draw_period_target = config
+            .target_fps
+            .filter(|fps| *fps > 0.)
+            .map(|fps| Duration::from_secs_f64(1.0 / fps));
         let winit_camera = WinitCameraAdapter::new(Camera::from(PerspectiveConfig {
             //OrthogonalConfig {
             ..Default::default()
         }));
-        let draw_context = draw_context::DrawContext::new(Arc::clone(&window), dimensions)
-            .await
-            .unwrap();
-        let scenario = S::new(&draw_context);
-        Self {
+        #[cfg(feature = "gamepad")]
+        let gamepad = gilrs::Gilrs::new()
+            .inspect_err(|err| warn!("Gamepad support not available: {err}"))
+            .ok();
+        let draw_context = draw_context::DrawContext::new_with_config(
+            Arc::clone(&window),
+            dimensions,
+            &config.draw_context_config,
+        )
+        .await?;
+        #[cfg(feature = "egui")]
+        let gui = EguiOverlay::new(&window, &draw_context);
+        let scenario = S::new(&draw_context)?;
+        Ok(Self {
             window,
             mouse_state,
-            scenario_start,
-            last_draw_instant,
+            input_state: InputState::new(),
+            touch_state: TouchState::new(),
+            update_timer: UpdateTimer::new(config.clock),
+            last_frame_instant: Instant::now(),
             draw_period_target,
+            fullscreen_key: config.fullscreen_key,
+            screenshot_key: config.screenshot_key,
+            projection_key: config.projection_key,
+            pause_key: config.pause_key,
+            slow_down_key: config.slow_down_key,
+            speed_up_key: config.speed_up_key,
+            update_mode: config.update_mode,
+            redraw_mode: config.redraw_mode,
+            redraw_pending: true,
+            time_control: TimeControl::new(),
+            frame_counter: FrameCounter::new(),
+            last_render_stats: RenderStats::default(),
+            minimized: false,
             winit_camera,
+            #[cfg(feature = "gamepad")]
+            gamepad,
             draw_context,
+            #[cfg(feature = "egui")]
+            gui,
             scenario,
+        })
+    }
+
+    fn camera_mut(&mut self) -> &mut WinitCameraAdapter {
+        active_camera(&mut self.scenario, &mut self.winit_camera)
+    }
+
+    fn update_interval(&mut self, update_step: UpdateStep) -> UpdateInterval {
+        let camera = self.camera_mut();
+        let (camera_view, camera_matrix) = (camera.as_ref().view, camera.get_camera_matrix());
+        UpdateInterval {
+            scenario_start: self.update_timer.scenario_start(),
+            scenario_elapsed: update_step.scenario_elapsed,
+            update_delta: update_step.update_delta,
+            fps: self.frame_counter.instant_fps(),
+            average_fps: self.frame_counter.average_fps(),
+            camera_view,
+            camera_matrix,
+            last_render_stats: self.last_render_stats,
+            cursor: self.cursor_position(),
+            input: self.input_state.clone(),
+        }
+    }
+
+    fn cursor_position(&self) -> Option<CursorPosition> {
+        let surface_config = &self.draw_context.surface_config;
+        self.mouse_state.cursor_position.map(|position| {
+            CursorPosition::new(
+                [position.x as f32, position.y as f32],
+                surface_config.width,
+                surface_config.height,
+            )
+        })
+    }
+
+    // Resolution is the render size, so it follows the resizes and the render scale. Mouse
+    // coordinates are scaled the same way and flipped vertically, the shadertoy origin being the
+    // bottom-left corner
+    fn canvas_uniforms(&self) -> CanvasUniforms {
+        let Dimensions { width, height } = self.draw_context.render_size();
+        let (width, height) = (width as f32, height as f32);
+        let scale = self.draw_context.render_scale();
+        let to_canvas = |position: Option<PhysicalPosition<f64>>| {
+            position.map_or([0.0; 2], |p| {
+                [p.x as f32 * scale, height - p.y as f32 * scale]
+            })
+        };
+        let [mouse_x, mouse_y] = to_canvas(self.mouse_state.cursor_position);
+        let [click_x, click_y] = to_canvas(self.mouse_state.last_click_position);
+        CanvasUniforms {
+            time: self.update_timer.scenario_elapsed().as_secs_f32(),
+            resolution: [width, height],
+            mouse: [mouse_x, mouse_y, click_x, click_y],
+        }
+    }
+
+    // On the web, winit goes through the Fullscreen API, which the browser allows as the
+    // request comes from a key press
+    fn toggle_fullscreen(&self) {
+        if self.window.fullscreen().is_some() {
+            self.window.set_fullscreen(None);
+        } else {
+            self.window
+                .set_fullscreen(Some(Fullscreen::Borderless(None)));
+        }
+    }
+
+    fn update_scenario(&mut self) {
+        let update_steps = self
+            .update_timer
+            .tick(&self.time_control, &self.update_mode);
+        for update_step in update_steps {
+            let update_interval = self.update_interval(update_step);
+            self.scenario.update(&self.draw_context, &update_interval);
+            self.input_state.clear_edges();
         }
     }
 }
 
 struct AppHandlerState<S: 'static> {
     state: Option<App<S>>,
+    config: Option<AppConfig>,
     event_loop_proxy: Option<EventLoopProxy<App<S>>>,
 }
 
 impl<S> AppHandlerState<S> {
-    fn new(event_loop: &EventLoop<App<S>>) -> Self {
+    fn new(event_loop: &EventLoop<App<S>>, config: AppConfig) -> Self {
         Self {
             state: None,
+            config: Some(config),
             event_loop_proxy: Some(event_loop.create_proxy()),
         }
     }
@@ -147,12 +480,13 @@ impl<S: Scenario + 'static> ApplicationHandler<App<S>> for AppHandlerState<S> {
         if self.state.is_some() {
             return;
         }
-        #[allow(unused_mut)]
-        let mut window_attributes = Window::default_attributes();
+        let config = self.config.take().unwrap_or_default();
+        #[cfg(not(target_arch = "wasm32"))]
+        let window_attributes = config.window_config.window_attributes();
         #[allow(unused_mut)]
         let mut dimensions = None;
         #[cfg(target_arch = "wasm32")]
-        {
+        let window_attributes = {
             use wasm_bindgen::JsCast;
             use winit::dpi::PhysicalSize;
             use winit::platform::web::WindowAttributesExtWebSys;
@@ -164,26 +498,33 @@ impl<S: Scenario + 'static> ApplicationHandler<App<S>> for AppHandlerState<S> {
             let height = dom_window.inner_height().unwrap().as_f64().unwrap() as u32;
             dimensions.replace(Dimensions { width, height });
             // FIXME winit window has size of 0 at startup, so also passing dimensions to draw context
-            window_attributes = window_attributes
+            Window::default_attributes()
                 .with_canvas(Some(canvas))
-                .with_inner_size(PhysicalSize::new(width, height));
-        }
+                .with_inner_size(PhysicalSize::new(width, height))
+        };
         let window = event_loop.create_window(window_attributes).unwrap();
         window.set_cursor(CursorIcon::Grab);
-        let app_future = App::<S>::async_new(window, dimensions);
+        let app_future = App::<S>::async_new(window, dimensions, config);
         let event_loop_proxy = self.event_loop_proxy.take().unwrap();
         #[cfg(target_arch = "wasm32")]
         {
             wasm_bindgen_futures::spawn_local(async move {
-                let app = app_future.await;
-                assert!(event_loop_proxy.send_event(app).is_ok());
+                match app_future.await {
+                    Ok(app) => assert!(event_loop_proxy.send_event(app).is_ok()),
+                    Err(err) => error!("Could not initialize app: {err:#}"),
+                }
             });
         }
         #[cfg(not(target_arch = "wasm32"))]
         {
             use pollster::FutureExt;
-            let app = app_future.block_on();
-            assert!(event_loop_proxy.send_event(app).is_ok());
+            match app_future.block_on() {
+                Ok(app) => assert!(event_loop_proxy.send_event(app).is_ok()),
+                Err(err) => {
+                    error!("Could not initialize app: {err:#}");
+                    event_loop.exit();
+                }
+            }
         }
     }
 
@@ -195,6 +536,13 @@ impl<S: Scenario + 'static> ApplicationHandler<App<S>> for AppHandlerState<S> {
         let Some(ref mut app) = self.state else {
             return;
         };
+        if !matches!(event, WindowEvent::RedrawRequested) {
+            app.redraw_pending = true;
+        }
+        #[cfg(feature = "egui")]
+        if app.gui.window_event(&app.window, &event) {
+            return;
+        }
         match event {
             WindowEvent::CloseRequested => {
                 debug!("Closing app");
@@ -203,11 +551,47 @@ impl<S: Scenario + 'static> ApplicationHandler<App<S>> for AppHandlerState<S> {
             WindowEvent::Resized(physical_size) => {
                 debug!("Window is resizing");
                 app.mouse_state.resize_action(&app.window);
+                let minimized = physical_size.width == 0 || physical_size.height == 0;
+                if app.minimized && !minimized {
+                    // So the time spent minimized is not seen as one long frame
+                    app.update_timer.skip_elapsed();
+                    app.last_frame_instant = Instant::now();
+                }
+                app.minimized = minimized;
                 app.draw_context
                     .resize(physical_size.width, physical_size.height);
+                if physical_size.height > 0 {
+                    let aspect_ratio = app.draw_context.aspect_ratio();
+                    app.camera_mut().as_mut().set_aspect(aspect_ratio);
+                }
             }
             WindowEvent::KeyboardInput { ref event, .. } => {
-                app.winit_camera.keyboard_event_listener(event);
+                if event.state == ElementState::Pressed && !event.repeat {
+                    let pressed_key = Some(event.physical_key);
+                    if app.fullscreen_key.map(PhysicalKey::Code) == pressed_key {
+                        app.toggle_fullscreen();
+                    }
+                    if app.screenshot_key.map(PhysicalKey::Code) == pressed_key {
+                        app.draw_context.request_frame_capture();
+                    }
+                    if app.projection_key.map(PhysicalKey::Code) == pressed_key {
+                        app.camera_mut().toggle_projection_mode();
+                    }
+                    if app.pause_key.map(PhysicalKey::Code) == pressed_key {
+                        app.time_control.toggle_pause();
+                    }
+                    if app.slow_down_key.map(PhysicalKey::Code) == pressed_key {
+                        app.time_control.slow_down();
+                    }
+                    if app.speed_up_key.map(PhysicalKey::Code) == pressed_key {
+                        app.time_control.speed_up();
+                    }
+                }
+                app.input_state.keyboard_event_listener(event);
+                app.camera_mut().keyboard_event_listener(event);
+            }
+            WindowEvent::Focused(false) => {
+                app.input_state.release_all();
             }
             WindowEvent::Moved { .. } => {
                 debug!("Window moved");
@@ -217,29 +601,76 @@ impl<S: Scenario + 'static> ApplicationHandler<App<S>> for AppHandlerState<S> {
                 app.mouse_state.is_cursor_inside = true;
             }
             WindowEvent::CursorLeft { .. } => {
-                app.mouse_state.is_cursor_inside = false;
+                app.mouse_state.cursor_left_action();
             }
-            WindowEvent::MouseInput { state, button, .. } => {
-                // Works with WASM and browser canvas
-                if button == MouseButton::Left {
-                    app.mouse_state
-                        .left_button_action(state, app.window.as_ref());
-                }
+            WindowEvent::CursorMoved { position, .. } => {
+                app.mouse_state.cursor_moved_action(position);
             }
-            WindowEvent::RedrawRequested { .. } => {
-                let update_delta = app.last_draw_instant.elapsed();
-                app.last_draw_instant = Instant::now();
-                app.scenario.update(
-                    &app.draw_context,
-                    &UpdateInterval {
-                        scenario_start: app.scenario_start,
-                        update_delta,
-                    },
-                );
-                app.winit_camera.update();
-                app.draw_context
-                    .set_projection(app.winit_camera.get_camera_matrix());
-                app.draw_context.render_scene(&app.scenario).unwrap();
+            // Works with WASM and browser canvas
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                app.mouse_state
+                    .left_button_action(state, app.window.as_ref());
+            }
+            WindowEvent::Touch(ref touch) => {
+                let camera = active_camera(&mut app.scenario, &mut app.winit_camera);
+                app.touch_state.touch_action(touch, camera);
+            }
+            WindowEvent::RedrawRequested => {
+                if let Some(reason) = app.draw_context.device_lost_reason() {
+                    error!("Cannot render anymore, the device was lost: {reason}");
+                    event_loop.exit();
+                    return;
+                }
+                if app.minimized {
+                    return;
+                }
+                let frame_instant = Instant::now();
+                app.frame_counter
+                    .push_frame(frame_instant.saturating_duration_since(app.last_frame_instant));
+                app.last_frame_instant = frame_instant;
+                #[cfg(feature = "gamepad")]
+                if let Some(ref mut gamepad) = app.gamepad {
+                    while let Some(gilrs::Event { event, .. }) = gamepad.next_event() {
+                        active_camera(&mut app.scenario, &mut app.winit_camera)
+                            .gamepad_event_listener(&event);
+                    }
+                }
+                let reverse_z = app.draw_context.is_reverse_z();
+                let camera = app.camera_mut();
+                camera.update();
+                camera.as_mut().set_reverse_z(reverse_z);
+                app.update_scenario();
+                let camera_matrix = app.camera_mut().get_camera_matrix();
+                app.draw_context.set_projection(camera_matrix);
+                app.draw_context.set_canvas_uniforms(&app.canvas_uniforms());
+                #[cfg(feature = "egui")]
+                let render_result = {
+                    app.gui.prepare(&app.window, &mut app.scenario);
+                    app.draw_context
+                        .render_scene_with_overlay(&mut app.scenario, &mut app.gui)
+                };
+                #[cfg(not(feature = "egui"))]
+                let render_result = app.draw_context.render_scene(&mut app.scenario);
+                match render_result {
+                    Ok(render_stats) => app.last_render_stats = render_stats,
+                    Err(err) => {
+                        error!("Could not render scene: {err:#}");
+                        event_loop.exit();
+                    }
+                }
+                match app.draw_context.take_captured_frame() {
+                    Ok(Some(frame)) => {
+                        if let Err(err) = save_screenshot(&frame.into_image()) {
+                            error!("Could not save screenshot: {err:#}");
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => error!("Could not capture screenshot: {err:#}"),
+                }
             }
             _ => {}
         }
@@ -262,7 +693,8 @@ impl<S: Scenario + 'static> ApplicationHandler<App<S>> for AppHandlerState<S> {
             }
         }
         if app.mouse_state.is_mouse_rotation_enabled() {
-            app.winit_camera.mouse_event_listener(&event);
+            app.camera_mut().mouse_event_listener(&event);
+            app.redraw_pending = true;
         }
     }
 
@@ -270,21 +702,103 @@ impl<S: Scenario + 'static> ApplicationHandler<App<S>> for AppHandlerState<S> {
         let Some(ref mut app) = self.state else {
             return;
         };
-        let since_last_draw = app.last_draw_instant.elapsed();
-        if since_last_draw >= app.draw_period_target {
+        if app.minimized {
+            // Woken up by the next resize event
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+        if app.redraw_mode == RedrawMode::OnDemand {
+            let key_held = app.input_state.held_keys().next().is_some();
+            if app.redraw_pending || key_held || app.scenario.needs_redraw() {
+                app.redraw_pending = false;
+                app.window.as_ref().request_redraw();
+            }
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+        // Without vsync, the target FPS would cap a present mode chosen to be uncapped
+        let draw_period_target = app
+            .draw_period_target
+            .filter(|_| app.draw_context.is_vsync());
+        let Some(draw_period_target) = draw_period_target else {
+            app.window.as_ref().request_redraw();
+            event_loop.set_control_flow(ControlFlow::Poll);
+            return;
+        };
+        let since_last_draw = Instant::now().saturating_duration_since(app.last_frame_instant);
+        if since_last_draw >= draw_period_target {
             app.window.as_ref().request_redraw();
             event_loop.set_control_flow(ControlFlow::Poll);
         } else {
             event_loop.set_control_flow(ControlFlow::WaitUntil(
-                Instant::now() + app.draw_period_target - since_last_draw,
+                Instant::now() + draw_period_target - since_last_draw,
             ));
         }
     }
 }
 
+fn encode_png(image: &image::RgbaImage) -> anyhow::Result<Vec<u8>> {
+    use image::ImageEncoder;
+    let mut png = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut png).write_image(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        image::ExtendedColorType::Rgba8,
+    )?;
+    Ok(png)
+}
+
+fn save_screenshot(image: &image::RgbaImage) -> anyhow::Result<()> {
+    let png = encode_png(image)?;
+    let filename = format!(
+        "screenshot-{}.png",
+        chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use anyhow::Context;
+        std::fs::write(&filename, png).with_context(|| format!("Cannot write {filename}"))?;
+        info!("Screenshot saved to {filename}");
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        // Triggers a browser download through a temporary link to the PNG blob
+        use anyhow::anyhow;
+        use wasm_bindgen::JsCast;
+        let js_error = |err| anyhow!("{err:?}");
+        let png_array = js_sys::Uint8Array::from(png.as_slice());
+        let blob_parts = js_sys::Array::of1(&png_array);
+        let blob_options = web_sys::BlobPropertyBag::new();
+        blob_options.set_type("image/png");
+        let blob =
+            web_sys::Blob::new_with_u8_array_sequence_and_options(&blob_parts, &blob_options)
+                .map_err(js_error)?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| anyhow!("No document available"))?;
+        let link = document
+            .create_element("a")
+            .map_err(js_error)?
+            .dyn_into::<web_sys::HtmlAnchorElement>()
+            .map_err(|_| anyhow!("Created element is not a link"))?;
+        link.set_href(&url);
+        link.set_download(&filename);
+        link.click();
+        web_sys::Url::revoke_object_url(&url).map_err(js_error)?;
+        info!("Screenshot {filename} downloaded");
+    }
+    Ok(())
+}
+
 pub fn init_event_loop<S: Scenario + 'static>() {
+    init_event_loop_with_config::<S>(AppConfig::default());
+}
+
+pub fn init_event_loop_with_config<S: Scenario + 'static>(config: AppConfig) {
     let event_loop = EventLoop::with_user_event().build().unwrap();
     event_loop.set_control_flow(ControlFlow::Poll);
-    let app_handler_state = &mut AppHandlerState::<S>::new(&event_loop);
+    let app_handler_state = &mut AppHandlerState::<S>::new(&event_loop, config);
     event_loop.run_app(app_handler_state).unwrap();
 }